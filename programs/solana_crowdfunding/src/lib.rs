@@ -1,6 +1,395 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
 
+pub const CONTRIBUTION_REBATE: u64 = 5_000;
+pub const MAX_NAME_LEN: usize = 32;
+pub const MAX_STRINGS_TOTAL: usize = 200;
+pub const LOYALTY_LAMPORTS_PER_POINT: u64 = 1_000_000;
+pub const DEFAULT_BACKUP_INACTIVITY_TIMEOUT: i64 = 180 * 24 * 60 * 60;
+pub const DEFAULT_CHARITY_TIMEOUT: i64 = 365 * 24 * 60 * 60;
+pub const DEFAULT_ABANDON_WINDOW: i64 = 365 * 24 * 60 * 60;
+pub const DEFAULT_ABANDON_PENALTY_BPS: u16 = 500;
+pub const DEFAULT_MIN_AGE_BEFORE_CANCEL: i64 = 60 * 60;
+pub const KEEPER_REWARD: u64 = 5_000;
+pub const CONTRIBUTION_INIT_IF_NEEDED: u8 = 0;
+pub const CONTRIBUTION_INIT_EXPLICIT: u8 = 1;
+pub const DEFAULT_SALVAGE_WINDOW: i64 = 24 * 60 * 60;
+pub const SALVAGE_MIN_RAISED_BPS: u64 = 9_000;
+pub const MAX_FEE_BPS: u16 = 1_000;
+pub const MAX_BATCH_CREATE: usize = 5;
+pub const MAX_BATCH_UPDATE: usize = 10;
+pub const MAX_DESCRIPTION_CHUNKS: u8 = 16;
+pub const MAX_CHUNK_LEN: usize = 512;
+pub const MAX_MICRO_ENTRIES: usize = 32;
+pub const MAX_MICRO_CONTRIBUTION: u64 = 1_000_000;
+pub const MAX_STATE_LOG_ENTRIES: usize = 16;
+pub const HISTOGRAM_BINS: usize = 30;
+pub const MAX_BLOCKED_DONORS: usize = 8;
+pub const MAX_CLOCK_DRIFT_TOLERANCE: i64 = 60;
+pub const MAX_COMMENT_LEN: usize = 280;
+pub const MAX_LOCK_AFTER_DEADLINE: i64 = 30 * 24 * 60 * 60;
+
+// Campaign::filter_key layout, little-endian at FILTER_KEY_OFFSET so a single
+// memcmp can match it:
+//   bits 0..8   category
+//   bits 8..16  status (STATUS_*)
+//   bit  16     verified by the platform
+//   bit  17     listed (visible to indexers)
+pub const FILTER_KEY_OFFSET: usize = 8 + 32 + 8 + 8 + 8 + 1 + 1 + 8;
+pub const FILTER_VERIFIED_BIT: u64 = 1 << 16;
+pub const FILTER_LISTED_BIT: u64 = 1 << 17;
+pub const STATUS_ACTIVE: u8 = 0;
+pub const STATUS_GOAL_MET: u8 = 1;
+pub const STATUS_CLAIMED: u8 = 2;
+pub const STATUS_REFUNDING: u8 = 3;
+pub const STATUS_CANCELLED: u8 = 4;
+pub const FUNDING_MODE_ALL_OR_NOTHING: u8 = 0;
+pub const FUNDING_MODE_KEEP_WHAT_YOU_RAISE: u8 = 1;
+pub const ETA_UNKNOWN: i64 = -1;
+pub const ACTION_WITHDRAW: u8 = 0;
+pub const ACTION_CANCEL: u8 = 1;
+// 2 is reserved and is_authorized rejects it as UnknownAction.
+pub const ACTION_UPDATE: u8 = 3;
+pub const ACTION_MODERATE: u8 = 4;
+
+pub fn fee_for(amount: u64, fee_bps: u16) -> u64 {
+    (amount as u128 * fee_bps as u128 / 10_000) as u64
+}
+
+// The refund that closes out the last remaining contributor also takes whatever
+// remainder is left in the vault, so no lamports are stranded once every backer
+// is repaid.
+pub fn refund_split(amount: u64, fee_bps: u16, vault_balance: u64, is_last: bool) -> (u64, u64) {
+    let fee = fee_for(amount, fee_bps);
+    let gross = if is_last { vault_balance.max(amount) } else { amount };
+    (gross - fee, fee)
+}
+
+pub fn transfer_from_vault<'info>(
+    system_program: &Program<'info, System>,
+    vault: &SystemAccount<'info>,
+    vault_bump: u8,
+    campaign_key: Pubkey,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let seeds = &[
+        b"vault",
+        campaign_key.as_ref(),
+        &[vault_bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_context = CpiContext::new_with_signer(
+        system_program.to_account_info(),
+        system_program::Transfer {
+            from: vault.to_account_info(),
+            to,
+        },
+        signer_seeds,
+    );
+    system_program::transfer(cpi_context, amount)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn pay_out_vault<'info>(
+    system_program: &Program<'info, System>,
+    vault: &SystemAccount<'info>,
+    vault_bump: u8,
+    campaign_key: Pubkey,
+    recipient: AccountInfo<'info>,
+    treasury: AccountInfo<'info>,
+    fee_bps: u16,
+    available: u64,
+) -> Result<(u64, u64)> {
+    let fee = fee_for(available, fee_bps);
+
+    if fee > 0 {
+        transfer_from_vault(system_program, vault, vault_bump, campaign_key, treasury, fee)?;
+    }
+    transfer_from_vault(system_program, vault, vault_bump, campaign_key, recipient, available - fee)?;
+
+    Ok((available - fee, fee))
+}
+
+// Every payout path (creator withdraw, backup takeover, abandoned finalize)
+// settles through here so the review block, approver co-sign, tranche
+// vesting and the first-backer incentive apply whoever triggers it.
+#[allow(clippy::too_many_arguments)]
+pub fn settle_payout<'info>(
+    campaign: &mut Account<'info, Campaign>,
+    platform: &PlatformConfig,
+    system_program: &Program<'info, System>,
+    vault: &SystemAccount<'info>,
+    vault_bump: u8,
+    recipient: AccountInfo<'info>,
+    treasury: AccountInfo<'info>,
+    fee_bps: u16,
+    approver: Option<&Signer<'info>>,
+    first_contributor: Option<&SystemAccount<'info>>,
+    now: i64,
+) -> Result<(u64, u64)> {
+    if platform.report_threshold != 0 && campaign.report_count >= platform.report_threshold {
+        return err!(CrowdfundError::CampaignUnderReview);
+    }
+    if campaign.approver_required {
+        match approver {
+            Some(approver) if approver.key() == campaign.approver => {}
+            _ => return err!(CrowdfundError::ApproverSignatureRequired),
+        }
+    }
+
+    let campaign_key = campaign.key();
+    let available = campaign.withdrawable(vault.lamports())?;
+
+    let (payout, fee) = if campaign.tranche_count > 1 {
+        let fee = fee_for(available, fee_bps);
+        if fee > 0 {
+            transfer_from_vault(system_program, vault, vault_bump, campaign_key, treasury, fee)?;
+        }
+        campaign.tranche_total = available - fee;
+        campaign.tranche_recipient = recipient.key();
+        campaign.claimed_at = now;
+        let first = campaign.tranche_unlocked(now);
+        transfer_from_vault(system_program, vault, vault_bump, campaign_key, recipient, first)?;
+        campaign.tranche_released = first;
+        (first, fee)
+    } else {
+        pay_out_vault(system_program, vault, vault_bump, campaign_key, recipient, treasury, fee_bps, available)?
+    };
+    campaign.record_withdrawal(payout + fee)?;
+
+    let incentive = campaign.first_backer_incentive;
+    if incentive > 0 && campaign.first_contributor != Pubkey::default() {
+        let first = match first_contributor {
+            Some(first) if first.key() == campaign.first_contributor => first,
+            _ => return err!(CrowdfundError::InvalidFirstContributor),
+        };
+        **campaign.to_account_info().try_borrow_mut_lamports()? -= incentive;
+        **first.to_account_info().try_borrow_mut_lamports()? += incentive;
+        campaign.first_backer_incentive = 0;
+    }
+
+    campaign.claimed = true;
+    campaign.refresh_filter_key();
+    Ok((payout, fee))
+}
+
+pub fn validate_campaign_params(
+    name: &str,
+    metadata: &CampaignMetadata,
+    goal: u64,
+    deadline: i64,
+    raise_cap: u64,
+    now: i64,
+) -> Result<()> {
+    if name.is_empty() {
+        return err!(CrowdfundError::NameEmpty);
+    }
+    if name.len() > MAX_NAME_LEN {
+        return err!(CrowdfundError::NameTooLong);
+    }
+    if name.len() + metadata.uri.len() + metadata.symbol.len() > MAX_STRINGS_TOTAL {
+        return err!(CrowdfundError::MetadataTooLarge);
+    }
+
+    if deadline <= now {
+        return err!(CrowdfundError::DeadlineInPast);
+    }
+    if raise_cap != 0 && raise_cap < goal {
+        return err!(CrowdfundError::RaiseCapBelowGoal);
+    }
+    Ok(())
+}
+
+pub fn check_plausibility(platform: &PlatformConfig, goal: u64, deadline: i64, now: i64) -> Result<()> {
+    if platform.goal_increment > 1 && goal % platform.goal_increment != 0 {
+        return err!(CrowdfundError::GoalNotAligned);
+    }
+
+    let duration = (deadline - now).max(1) as u128;
+    let goal_per_day = goal as u128 * 86_400 / duration;
+
+    let too_small = platform.min_goal_per_day != 0 && goal_per_day < platform.min_goal_per_day as u128;
+    let too_large = platform.max_goal_per_day != 0 && goal_per_day > platform.max_goal_per_day as u128;
+    if too_small || too_large {
+        if platform.enforce_plausibility {
+            return err!(CrowdfundError::ImplausibleCampaignParams);
+        }
+        msg!("Warning: goal of {} lamports over {} seconds looks implausible", goal, duration);
+    }
+    Ok(())
+}
+
+pub fn check_platform_capacity(platform: &PlatformConfig, stats: &PlatformStats, amount: u64) -> Result<()> {
+    if platform.max_tvl != 0 && stats.total_locked + amount > platform.max_tvl {
+        return err!(CrowdfundError::PlatformTvlCapReached);
+    }
+    Ok(())
+}
+
+// Returns the campaign's generation: campaign-derived PDAs are seeded with it, so
+// recreating a closed `creator + name` address never reuses the old sub-accounts.
+pub fn open_campaign_slot(platform: &PlatformConfig, stats: &mut PlatformStats) -> Result<u64> {
+    if platform.max_active_campaigns != 0 && stats.active_campaigns >= platform.max_active_campaigns {
+        return err!(CrowdfundError::TooManyActiveCampaigns);
+    }
+    stats.active_campaigns += 1;
+    stats.campaigns_created += 1;
+    Ok(stats.campaigns_created)
+}
+
+// Guards shared by every contribution entry point; the caller checks the
+// funding source and moves the lamports before `record_contribution`.
+pub fn prepare_contribution(
+    campaign: &mut Campaign,
+    platform: &PlatformConfig,
+    platform_stats: &PlatformStats,
+    donor: &Pubkey,
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    campaign.roll_if_due(now);
+    campaign.assert_accepts(donor, amount, now)?;
+    check_platform_capacity(platform, platform_stats, amount)
+}
+
+// Checked on top of the PDA seeds: `init_if_needed` hands back whatever already
+// lives at the address, so make sure it is this donor's record for this campaign.
+pub fn assert_contribution_account(
+    contribution: &Account<Contribution>,
+    campaign: &Pubkey,
+    donor: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let contribution_info = contribution.to_account_info();
+    if contribution_info.owner != program_id {
+        return err!(CrowdfundError::InvalidContributionAccount);
+    }
+    {
+        let data = contribution_info.try_borrow_data()?;
+        let discriminator = &data[..8];
+        if discriminator != [0u8; 8] && discriminator != Contribution::DISCRIMINATOR {
+            return err!(CrowdfundError::InvalidContributionAccount);
+        }
+    }
+    if contribution.donor != Pubkey::default() && (contribution.donor != *donor || contribution.campaign != *campaign) {
+        return err!(CrowdfundError::InvalidContributionAccount);
+    }
+    Ok(())
+}
+
+// Creator match, histogram and state log bookkeeping for `amount` lamports that
+// have already reached the vault, from a backer who had `previous` lamports in.
+// Holds no per-donor record, so micro pool entries are credited the same way.
+#[allow(clippy::too_many_arguments)]
+pub fn credit_campaign<'info>(
+    campaign: &mut Account<'info, Campaign>,
+    vault: &SystemAccount<'info>,
+    platform_stats: &mut PlatformStats,
+    state_log: Option<&mut Account<'info, StateLog>>,
+    histogram: Option<&mut Account<'info, ContributionHistogram>>,
+    donor: Pubkey,
+    new_backer: bool,
+    previous: u64,
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    let mut matched = amount.min(campaign.creator_match_pool);
+    if campaign.raise_cap != 0 {
+        matched = matched.min(campaign.raise_cap - (campaign.raised + amount));
+    }
+    if campaign.hard_cap {
+        matched = matched.min(campaign.goal - (campaign.raised + amount));
+    }
+    if matched > 0 {
+        **campaign.to_account_info().try_borrow_mut_lamports()? -= matched;
+        **vault.to_account_info().try_borrow_mut_lamports()? += matched;
+        campaign.creator_match_pool -= matched;
+        campaign.creator_matched += matched;
+    }
+
+    if new_backer {
+        campaign.contributors += 1;
+    }
+    campaign.track_counted(previous, previous + amount);
+    let creator_blocked = campaign.block_creator_first && donor == campaign.creator;
+    if campaign.first_contributor == Pubkey::default() && !creator_blocked {
+        campaign.first_contributor = donor;
+    }
+    let was_empty = campaign.raised == 0 && !campaign.refunds_started;
+    let was_below_goal = !campaign.is_goal_met();
+    campaign.raised += amount + matched;
+    campaign.refresh_filter_key();
+    platform_stats.total_locked += amount + matched;
+    if campaign.raised == campaign.goal {
+        campaign.goal_exactly_met = true;
+    }
+    if was_below_goal && campaign.is_goal_met() {
+        emit!(GoalReached {
+            campaign: campaign.key(),
+            goal: campaign.goal,
+            raised: campaign.raised,
+        });
+    }
+    if let Some(log) = state_log {
+        if was_empty {
+            log.record(CampaignState::Active, now, donor);
+        }
+        if was_below_goal && campaign.is_goal_met() {
+            log.record(CampaignState::Succeeded, now, donor);
+        }
+    }
+
+    if let Some(histogram) = histogram {
+        histogram.record(now, amount);
+    }
+    Ok(())
+}
+
+// `credit_campaign` plus the donor's contribution and loyalty records.
+#[allow(clippy::too_many_arguments)]
+pub fn record_contribution<'info>(
+    campaign: &mut Account<'info, Campaign>,
+    vault: &SystemAccount<'info>,
+    contribution: &mut Account<'info, Contribution>,
+    contribution_bump: u8,
+    loyalty: &mut Account<'info, LoyaltyAccount>,
+    loyalty_bump: u8,
+    platform_stats: &mut PlatformStats,
+    state_log: Option<&mut Account<'info, StateLog>>,
+    histogram: Option<&mut Account<'info, ContributionHistogram>>,
+    donor: Pubkey,
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    credit_campaign(
+        campaign,
+        vault,
+        platform_stats,
+        state_log,
+        histogram,
+        donor,
+        contribution.donor == Pubkey::default(),
+        contribution.amount,
+        amount,
+        now,
+    )?;
+
+    loyalty.donor = donor;
+    loyalty.bump = loyalty_bump;
+    loyalty.lifetime_contributed += amount;
+    loyalty.points += amount / LOYALTY_LAMPORTS_PER_POINT;
+
+    contribution.donor = donor;
+    contribution.campaign = campaign.key();
+    contribution.amount += amount;
+    contribution.bump = contribution_bump;
+    if campaign.in_soft_close(now) {
+        contribution.late = true;
+    }
+    Ok(())
+}
 
 declare_id!("5fwXYYbWEJaTQ2LWeMaWm6NWQAsQjKqBRuWHe4g8EY9f");
 
@@ -9,65 +398,309 @@ pub mod solana_crowdfunding {
     use super::*;
 
 
-    pub fn create_campaign(ctx: Context<Create>, goal: u64, deadline: i64) -> Result<()> {
+    pub fn create_campaign(
+        ctx: Context<Create>,
+        name: String,
+        goal: u64,
+        deadline: i64,
+        raise_cap: u64,
+        backup_creator: Option<Pubkey>,
+        metadata: CampaignMetadata,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+
+        validate_campaign_params(&name, &metadata, goal, deadline, raise_cap, clock.unix_timestamp)?;
+        check_plausibility(&ctx.accounts.platform, goal, deadline, clock.unix_timestamp)?;
+        let generation = open_campaign_slot(&ctx.accounts.platform, &mut ctx.accounts.platform_stats)?;
+
+        campaign.init(*ctx.accounts.creator.key, ctx.bumps.campaign, name, goal, deadline, raise_cap);
+        campaign.generation = generation;
+        campaign.duration = deadline - clock.unix_timestamp;
+        campaign.created_at = clock.unix_timestamp;
+        campaign.clock_drift_tolerance = ctx.accounts.platform.clock_drift_tolerance;
+        campaign.uri = metadata.uri;
+        campaign.symbol = metadata.symbol;
+        campaign.backup_creator = backup_creator.unwrap_or_default();
+
+        msg!("Campaign created! Name: {}, Goal: {} lamports, Deadline: {}", campaign.name, goal, deadline);
+        Ok(())
+    }
+
+
+    pub fn create_campaign_dual(
+        ctx: Context<CreateDual>,
+        name: String,
+        goal: u64,
+        deadline: i64,
+        raise_cap: u64,
+        metadata: CampaignMetadata,
+        approver_required: bool,
+    ) -> Result<()> {
         let campaign = &mut ctx.accounts.campaign;
         let clock = Clock::get()?;
 
 
-        if deadline <= clock.unix_timestamp {
-            return err!(CrowdfundError::DeadlineInPast);
+        validate_campaign_params(&name, &metadata, goal, deadline, raise_cap, clock.unix_timestamp)?;
+        check_plausibility(&ctx.accounts.platform, goal, deadline, clock.unix_timestamp)?;
+        let generation = open_campaign_slot(&ctx.accounts.platform, &mut ctx.accounts.platform_stats)?;
+
+        campaign.init(*ctx.accounts.creator.key, ctx.bumps.campaign, name, goal, deadline, raise_cap);
+        campaign.generation = generation;
+        campaign.duration = deadline - clock.unix_timestamp;
+        campaign.created_at = clock.unix_timestamp;
+        campaign.clock_drift_tolerance = ctx.accounts.platform.clock_drift_tolerance;
+        campaign.uri = metadata.uri;
+        campaign.symbol = metadata.symbol;
+        campaign.approver = *ctx.accounts.approver.key;
+        campaign.approver_required = approver_required;
+
+        msg!("Campaign created! Name: {}, Goal: {} lamports, Deadline: {}, Approver: {}", campaign.name, goal, deadline, campaign.approver);
+        Ok(())
+    }
+
+
+    pub fn create_campaigns_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateCampaignsBatch<'info>>,
+        names: Vec<String>,
+        goals: Vec<u64>,
+        deadlines: Vec<i64>,
+    ) -> Result<()> {
+        let creator = &ctx.accounts.creator;
+        let clock = Clock::get()?;
+
+
+        if names.is_empty() || names.len() != goals.len() || names.len() != deadlines.len() {
+            return err!(CrowdfundError::BatchLengthMismatch);
         }
+        if names.len() > MAX_BATCH_CREATE {
+            return err!(CrowdfundError::BatchTooLarge);
+        }
+        if ctx.remaining_accounts.len() != names.len() * 2 {
+            return err!(CrowdfundError::InvalidRemainingAccounts);
+        }
+
+        let metadata = CampaignMetadata { uri: String::new(), symbol: String::new() };
+        let lamports = Rent::get()?.minimum_balance(Campaign::LEN);
+
 
-        campaign.creator = *ctx.accounts.creator.key;
-        campaign.goal = goal;
-        campaign.raised = 0;
-        campaign.deadline = deadline;
-        campaign.claimed = false;
-        campaign.bump = ctx.bumps.campaign;
+        for (i, accounts) in ctx.remaining_accounts.chunks(2).enumerate() {
+            let (campaign_info, vault_info) = (&accounts[0], &accounts[1]);
+            let (name, goal, deadline) = (names[i].clone(), goals[i], deadlines[i]);
+
+            validate_campaign_params(&name, &metadata, goal, deadline, 0, clock.unix_timestamp)?;
+            check_plausibility(&ctx.accounts.platform, goal, deadline, clock.unix_timestamp)?;
+            let generation = open_campaign_slot(&ctx.accounts.platform, &mut ctx.accounts.platform_stats)?;
+
+            let (expected, bump) = Pubkey::find_program_address(
+                &[b"campaign", creator.key.as_ref(), name.as_bytes()],
+                ctx.program_id,
+            );
+            let (expected_vault, _) = Pubkey::find_program_address(
+                &[b"vault", expected.as_ref()],
+                ctx.program_id,
+            );
+            if campaign_info.key() != expected || vault_info.key() != expected_vault {
+                return err!(CrowdfundError::InvalidRemainingAccounts);
+            }
+
+
+            let seeds = &[
+                b"campaign",
+                creator.key.as_ref(),
+                name.as_bytes(),
+                &[bump],
+            ];
+            let signer_seeds = &[&seeds[..]];
+
+            let cpi_context = CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::CreateAccount {
+                    from: creator.to_account_info(),
+                    to: campaign_info.clone(),
+                },
+                signer_seeds,
+            );
+            system_program::create_account(cpi_context, lamports, Campaign::LEN as u64, ctx.program_id)?;
+
+            let mut campaign: Account<Campaign> = Account::try_from_unchecked(campaign_info)?;
+            campaign.init(creator.key(), bump, name, goal, deadline, 0);
+            campaign.generation = generation;
+            campaign.duration = deadline - clock.unix_timestamp;
+            campaign.created_at = clock.unix_timestamp;
+            campaign.clock_drift_tolerance = ctx.accounts.platform.clock_drift_tolerance;
+            campaign.exit(ctx.program_id)?;
+
+            msg!("Campaign created! Name: {}, Goal: {} lamports, Deadline: {}", campaign.name, goal, deadline);
+        }
 
-        msg!("Campaign created! Goal: {} lamports, Deadline: {}", goal, deadline);
         Ok(())
     }
 
 
     pub fn contribute(ctx: Context<Contribute>, amount: u64) -> Result<()> {
-        let campaign = &mut ctx.accounts.campaign;
-        
+        ctx.accounts.apply(&ctx.bumps, ctx.program_id, amount)
+    }
 
-        let cpi_context = CpiContext::new(
+
+    pub fn contribute_and_delegate(ctx: Context<Contribute>, amount: u64, delegate: Pubkey) -> Result<()> {
+        if ctx.accounts.campaign.block_self_delegation && delegate == ctx.accounts.donor.key() {
+            return err!(CrowdfundError::SelfDelegation);
+        }
+
+        ctx.accounts.apply(&ctx.bumps, ctx.program_id, amount)?;
+        ctx.accounts.contribution.delegate = delegate;
+
+        msg!("Delegated {} lamports of voting weight to {}", ctx.accounts.contribution.amount, delegate);
+        Ok(())
+    }
+
+
+    pub fn contribute_pct_of_remaining(ctx: Context<Contribute>, pct_bps: u16) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+
+        if pct_bps == 0 || pct_bps > 10_000 {
+            return err!(CrowdfundError::InvalidPercentage);
+        }
+        let remaining = campaign.goal.saturating_sub(campaign.raised);
+        if remaining == 0 {
+            return err!(CrowdfundError::GoalAlreadyMet);
+        }
+
+        let amount = (remaining as u128 * pct_bps as u128 / 10_000) as u64;
+        if amount == 0 {
+            return err!(CrowdfundError::PercentageRoundsToZero);
+        }
+        ctx.accounts.apply(&ctx.bumps, ctx.program_id, amount)?;
+
+        msg!("Covered {} bps of the remaining {} lamports: {}", pct_bps, remaining, amount);
+        Ok(())
+    }
+
+
+    pub fn contribute_referred(ctx: Context<ContributeReferred>, amount: u64, referrer: Pubkey) -> Result<()> {
+        if referrer == ctx.accounts.contribute.donor.key() {
+            return err!(CrowdfundError::SelfReferral);
+        }
+
+        ctx.accounts.contribute.apply(&ctx.bumps.contribute, ctx.program_id, amount)?;
+
+        let referral = &mut ctx.accounts.referral_stats;
+        referral.campaign = ctx.accounts.contribute.campaign.key();
+        referral.referrer = referrer;
+        referral.bump = ctx.bumps.referral_stats;
+        referral.referred_amount += amount;
+        referral.referred_count += 1;
+
+        msg!("Referred by {}: {} lamports", referrer, amount);
+        Ok(())
+    }
+
+
+    pub fn claim_referral_reward(ctx: Context<ClaimReferralReward>) -> Result<()> {
+        let referral = &mut ctx.accounts.referral_stats;
+        let treasury = &ctx.accounts.treasury;
+
+
+        if !ctx.accounts.campaign.claimed {
+            return err!(CrowdfundError::CampaignNotSuccessful);
+        }
+        if referral.reward_claimed {
+            return err!(CrowdfundError::RewardAlreadyClaimed);
+        }
+
+
+        let reward = fee_for(referral.referred_amount, ctx.accounts.platform.referral_reward_bps);
+        let rent = Rent::get()?;
+        if treasury.lamports() < reward + rent.minimum_balance(0) {
+            return err!(CrowdfundError::InsufficientTreasury);
+        }
+
+        let seeds = &[b"treasury".as_ref(), &[ctx.bumps.treasury]];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_context = CpiContext::new_with_signer(
             ctx.accounts.system_program.to_account_info(),
             system_program::Transfer {
-                from: ctx.accounts.donor.to_account_info(),
-                to: ctx.accounts.vault.to_account_info(),
+                from: treasury.to_account_info(),
+                to: ctx.accounts.referrer.to_account_info(),
             },
+            signer_seeds,
         );
-        system_program::transfer(cpi_context, amount)?;
+        system_program::transfer(cpi_context, reward)?;
 
-        campaign.raised += amount;
-        msg!("Contributed: {} lamports. Total Raised: {}", amount, campaign.raised);
+        referral.reward_claimed = true;
+        msg!("Referral reward paid: {} lamports", reward);
         Ok(())
     }
 
 
     pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+        ctx.accounts.apply(&ctx.bumps)
+    }
+
+
+    pub fn backup_withdraw(ctx: Context<BackupWithdraw>) -> Result<()> {
         let campaign = &mut ctx.accounts.campaign;
-        let creator = &mut ctx.accounts.creator;
-        let vault = &mut ctx.accounts.vault;
         let clock = Clock::get()?;
 
 
-        if campaign.raised < campaign.goal {
+        if campaign.cancelled {
+            return err!(CrowdfundError::CampaignCancelled);
+        }
+        if !campaign.is_payable() {
             return err!(CrowdfundError::GoalNotMet);
         }
-        if clock.unix_timestamp < campaign.deadline {
+        if !campaign.has_ended(clock.unix_timestamp) {
             return err!(CrowdfundError::CampaignNotEnded);
         }
         if campaign.claimed {
             return err!(CrowdfundError::AlreadyClaimed);
         }
+        if clock.unix_timestamp < campaign.deadline + ctx.accounts.platform.backup_inactivity_timeout {
+            return err!(CrowdfundError::BackupTimeoutNotElapsed);
+        }
+        campaign.assert_state_log(&ctx.accounts.state_log)?;
+
+
+        let (payout, fee) = settle_payout(
+            campaign,
+            &ctx.accounts.platform,
+            &ctx.accounts.system_program,
+            &ctx.accounts.vault,
+            ctx.bumps.vault,
+            ctx.accounts.backup_creator.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            ctx.accounts.platform.success_fee_bps,
+            ctx.accounts.approver.as_ref(),
+            ctx.accounts.first_contributor.as_ref(),
+            clock.unix_timestamp,
+        )?;
+
+        if let Some(log) = &mut ctx.accounts.state_log {
+            log.record(CampaignState::Claimed, clock.unix_timestamp, ctx.accounts.backup_creator.key());
+        }
+        let platform_stats = &mut ctx.accounts.platform_stats;
+        platform_stats.total_locked = platform_stats.total_locked.saturating_sub(campaign.raised);
+        campaign.finalize(platform_stats);
+        msg!("Backup creator withdrew: {} lamports (fee: {})", payout, fee);
+        Ok(())
+    }
 
 
-        let vault_balance = vault.lamports();
+    pub fn refund(ctx: Context<Refund>, amount: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let donor = &mut ctx.accounts.donor;
+        let vault = &mut ctx.accounts.vault;
+        let clock = Clock::get()?;
+
+
+        campaign.roll_if_due(clock.unix_timestamp);
+        campaign.assert_refundable(clock.unix_timestamp)?;
+        if amount > ctx.accounts.contribution.amount {
+            return err!(CrowdfundError::RefundExceedsContribution);
+        }
+        campaign.assert_state_log(&ctx.accounts.state_log)?;
 
 
         let campaign_key = campaign.key();
@@ -79,36 +712,71 @@ pub mod solana_crowdfunding {
         let signer_seeds = &[&seeds[..]];
 
 
+        let (payout, fee) = refund_split(
+            campaign.take_refund_share(amount),
+            ctx.accounts.platform.failure_fee_bps,
+            campaign.refundable_balance(vault.lamports())?,
+            campaign.contributors == 1 && amount == ctx.accounts.contribution.amount,
+        );
+        if fee > 0 {
+            let cpi_context = CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: vault.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                },
+                signer_seeds,
+            );
+            system_program::transfer(cpi_context, fee)?;
+        }
+
         let cpi_context = CpiContext::new_with_signer(
             ctx.accounts.system_program.to_account_info(),
             system_program::Transfer {
                 from: vault.to_account_info(),
-                to: creator.to_account_info(),
+                to: donor.to_account_info(),
             },
             signer_seeds,
         );
-        
-        system_program::transfer(cpi_context, vault_balance)?;
 
-        campaign.claimed = true;
-        msg!("Withdrawn all funds: {} lamports", vault_balance);
+        system_program::transfer(cpi_context, payout)?;
+
+        if !campaign.refunds_started {
+            if let Some(log) = &mut ctx.accounts.state_log {
+                log.record(CampaignState::Failed, clock.unix_timestamp, donor.key());
+            }
+        }
+        campaign.raised -= amount;
+        campaign.refunds_started = true;
+        campaign.refresh_filter_key();
+        ctx.accounts.platform_stats.total_locked = ctx.accounts.platform_stats.total_locked.saturating_sub(amount);
+        if ctx.accounts.platform.loyalty_clawback {
+            let loyalty = &mut ctx.accounts.loyalty;
+            loyalty.points = loyalty.points.saturating_sub(amount / LOYALTY_LAMPORTS_PER_POINT);
+        }
+        let contribution = &mut ctx.accounts.contribution;
+        campaign.track_counted(contribution.amount, contribution.amount - amount);
+        contribution.amount -= amount;
+        if contribution.amount == 0 {
+            campaign.contributors -= 1;
+            contribution.close(donor.to_account_info())?;
+        }
+
+        msg!("Refunded: {} lamports (fee: {})", payout, fee);
         Ok(())
     }
 
 
-    pub fn refund(ctx: Context<Refund>, amount: u64) -> Result<()> {
+    pub fn donate_refund(ctx: Context<DonateRefund>) -> Result<()> {
         let campaign = &mut ctx.accounts.campaign;
-        let donor = &mut ctx.accounts.donor;
+        let creator = &mut ctx.accounts.creator;
         let vault = &mut ctx.accounts.vault;
+        let amount = ctx.accounts.contribution.amount;
         let clock = Clock::get()?;
 
 
-        if clock.unix_timestamp < campaign.deadline {
-            return err!(CrowdfundError::CampaignNotEnded);
-        }
-        if campaign.raised >= campaign.goal {
-            return err!(CrowdfundError::GoalMetCannotRefund);
-        }
+        campaign.assert_refundable(clock.unix_timestamp)?;
+        campaign.assert_state_log(&ctx.accounts.state_log)?;
 
 
         let campaign_key = campaign.key();
@@ -120,99 +788,4636 @@ pub mod solana_crowdfunding {
         let signer_seeds = &[&seeds[..]];
 
 
+        let (payout, _) = refund_split(
+            campaign.take_refund_share(amount),
+            0,
+            campaign.refundable_balance(vault.lamports())?,
+            campaign.contributors == 1,
+        );
         let cpi_context = CpiContext::new_with_signer(
             ctx.accounts.system_program.to_account_info(),
             system_program::Transfer {
                 from: vault.to_account_info(),
-                to: donor.to_account_info(),
+                to: creator.to_account_info(),
             },
             signer_seeds,
         );
 
-        system_program::transfer(cpi_context, amount)?;
+        system_program::transfer(cpi_context, payout)?;
+
+        if !campaign.refunds_started {
+            if let Some(log) = &mut ctx.accounts.state_log {
+                log.record(CampaignState::Failed, clock.unix_timestamp, ctx.accounts.donor.key());
+            }
+        }
+        campaign.raised -= amount;
+        campaign.refunds_started = true;
+        campaign.refresh_filter_key();
+        campaign.contributors -= 1;
+        campaign.track_counted(amount, 0);
+        ctx.accounts.platform_stats.total_locked = ctx.accounts.platform_stats.total_locked.saturating_sub(amount);
 
-        msg!("Refunded: {} lamports", amount);
+        msg!("Refund of {} lamports donated to creator", payout);
         Ok(())
     }
-}
 
 
+    pub fn shrink_campaign(ctx: Context<ShrinkCampaign>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let campaign_info = campaign.to_account_info();
+        let current_len = campaign_info.data_len();
 
-#[derive(Accounts)]
-pub struct Create<'info> {
-    #[account(
-        init, 
-        payer = creator, 
-        space = 8 + 32 + 8 + 8 + 8 + 1 + 1,
-        seeds = [b"campaign", creator.key().as_ref()], 
-        bump
-    )]
-    pub campaign: Account<'info, Campaign>,
-    #[account(mut)]
-    pub creator: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
 
-#[derive(Accounts)]
-pub struct Contribute<'info> {
-    #[account(mut)]
-    pub campaign: Account<'info, Campaign>,
-    #[account(
-        mut,
-        seeds = [b"vault", campaign.key().as_ref()],
-        bump
-    )]
-    pub vault: SystemAccount<'info>,
-    #[account(mut)]
-    pub donor: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        if 8 + campaign.try_to_vec()?.len() > Campaign::LEN {
+            return err!(CrowdfundError::CampaignTooSmall);
+        }
+        if current_len <= Campaign::LEN {
+            msg!("Campaign account already compact: {} bytes", current_len);
+            return Ok(());
+        }
 
-#[derive(Accounts)]
-pub struct Withdraw<'info> {
-    #[account(
-        mut,
-        has_one = creator @ CrowdfundError::NotCreator
-    )]
-    pub campaign: Account<'info, Campaign>,
-    #[account(
-        mut,
-        seeds = [b"vault", campaign.key().as_ref()],
-        bump
-    )]
-    pub vault: SystemAccount<'info>,
-    #[account(mut)]
-    pub creator: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
 
-#[derive(Accounts)]
-pub struct Refund<'info> {
-    #[account(mut)]
-    pub campaign: Account<'info, Campaign>,
-    #[account(
-        mut,
-        seeds = [b"vault", campaign.key().as_ref()],
-        bump
-    )]
-    pub vault: SystemAccount<'info>,
-    #[account(mut)]
-    pub donor: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        // The match pool and first-backer incentive are held in campaign
+        // lamports and must stay behind with the rent.
+        let rent = Rent::get()?;
+        let held = rent.minimum_balance(Campaign::LEN) + campaign.creator_match_pool + campaign.first_backer_incentive;
+        let surplus = campaign_info.lamports().saturating_sub(held);
 
+        campaign_info.resize(Campaign::LEN)?;
+        **campaign_info.try_borrow_mut_lamports()? -= surplus;
+        **ctx.accounts.creator.to_account_info().try_borrow_mut_lamports()? += surplus;
 
+        msg!("Campaign shrunk from {} to {} bytes, refunded {} lamports", current_len, Campaign::LEN, surplus);
+        Ok(())
+    }
 
-#[account]
-pub struct Campaign {
-    pub creator: Pubkey,
+
+    pub fn initialize_platform(ctx: Context<InitializePlatform>, min_visible_raise: u64) -> Result<()> {
+        let platform = &mut ctx.accounts.platform;
+
+        platform.authority = *ctx.accounts.authority.key;
+        platform.min_visible_raise = min_visible_raise;
+        platform.bump = ctx.bumps.platform;
+        platform.salvage_window = DEFAULT_SALVAGE_WINDOW;
+        platform.backup_inactivity_timeout = DEFAULT_BACKUP_INACTIVITY_TIMEOUT;
+        platform.charity_timeout = DEFAULT_CHARITY_TIMEOUT;
+        platform.abandon_window = DEFAULT_ABANDON_WINDOW;
+        platform.abandon_penalty_bps = DEFAULT_ABANDON_PENALTY_BPS;
+        platform.min_age_before_cancel = DEFAULT_MIN_AGE_BEFORE_CANCEL;
+        platform.goal_increment = 1;
+        ctx.accounts.platform_stats.bump = ctx.bumps.platform_stats;
+
+
+        let rent = Rent::get()?;
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.authority.to_account_info(),
+                to: ctx.accounts.treasury.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, rent.minimum_balance(0))?;
+
+        msg!("Platform initialized! Authority: {}", platform.authority);
+        Ok(())
+    }
+
+
+    pub fn set_min_visible_raise(ctx: Context<UpdatePlatform>, min_visible_raise: u64) -> Result<()> {
+        ctx.accounts.platform.min_visible_raise = min_visible_raise;
+
+        msg!("Min visible raise set: {} lamports", min_visible_raise);
+        Ok(())
+    }
+
+
+    pub fn set_fee_rates(ctx: Context<UpdatePlatform>, success_fee_bps: u16, failure_fee_bps: u16) -> Result<()> {
+        if success_fee_bps > MAX_FEE_BPS || failure_fee_bps > MAX_FEE_BPS {
+            return err!(CrowdfundError::FeeTooHigh);
+        }
+
+        let platform = &mut ctx.accounts.platform;
+        platform.success_fee_bps = success_fee_bps;
+        platform.failure_fee_bps = failure_fee_bps;
+
+        msg!("Fee rates set! Success: {} bps, Failure: {} bps", success_fee_bps, failure_fee_bps);
+        Ok(())
+    }
+
+
+    pub fn set_max_tvl(ctx: Context<UpdatePlatform>, max_tvl: u64) -> Result<()> {
+        ctx.accounts.platform.max_tvl = max_tvl;
+
+        msg!("Max TVL set: {} lamports", max_tvl);
+        Ok(())
+    }
+
+
+    pub fn set_loyalty_clawback(ctx: Context<UpdatePlatform>, loyalty_clawback: bool) -> Result<()> {
+        ctx.accounts.platform.loyalty_clawback = loyalty_clawback;
+
+        msg!("Loyalty clawback on refund: {}", loyalty_clawback);
+        Ok(())
+    }
+
+
+    pub fn set_backup_inactivity_timeout(ctx: Context<UpdatePlatform>, backup_inactivity_timeout: i64) -> Result<()> {
+        ctx.accounts.platform.backup_inactivity_timeout = backup_inactivity_timeout;
+
+        msg!("Backup inactivity timeout set: {} seconds", backup_inactivity_timeout);
+        Ok(())
+    }
+
+
+    pub fn set_referral_reward_bps(ctx: Context<UpdatePlatform>, referral_reward_bps: u16) -> Result<()> {
+        if referral_reward_bps > MAX_FEE_BPS {
+            return err!(CrowdfundError::FeeTooHigh);
+        }
+        ctx.accounts.platform.referral_reward_bps = referral_reward_bps;
+
+        msg!("Referral reward set: {} bps", referral_reward_bps);
+        Ok(())
+    }
+
+
+    pub fn set_salvage_window(ctx: Context<UpdatePlatform>, salvage_window: i64) -> Result<()> {
+        ctx.accounts.platform.salvage_window = salvage_window;
+
+        msg!("Salvage window set: {} seconds", salvage_window);
+        Ok(())
+    }
+
+
+    pub fn set_goal_reduction_delay(ctx: Context<UpdatePlatform>, goal_reduction_delay: i64) -> Result<()> {
+        ctx.accounts.platform.goal_reduction_delay = goal_reduction_delay;
+
+        msg!("Goal reduction delay set: {} seconds", goal_reduction_delay);
+        Ok(())
+    }
+
+
+    pub fn set_report_threshold(ctx: Context<UpdatePlatform>, report_threshold: u32) -> Result<()> {
+        ctx.accounts.platform.report_threshold = report_threshold;
+
+        msg!("Report threshold set: {}", report_threshold);
+        Ok(())
+    }
+
+
+    pub fn set_charity_timeout(ctx: Context<UpdatePlatform>, charity_timeout: i64) -> Result<()> {
+        ctx.accounts.platform.charity_timeout = charity_timeout;
+
+        msg!("Charity timeout set: {} seconds", charity_timeout);
+        Ok(())
+    }
+
+
+    pub fn set_max_active_campaigns(ctx: Context<UpdatePlatform>, max_active_campaigns: u32) -> Result<()> {
+        ctx.accounts.platform.max_active_campaigns = max_active_campaigns;
+
+        msg!("Max active campaigns set: {}", max_active_campaigns);
+        Ok(())
+    }
+
+
+    pub fn set_abandon_policy(ctx: Context<UpdatePlatform>, abandon_window: i64, abandon_penalty_bps: u16) -> Result<()> {
+        if abandon_penalty_bps > MAX_FEE_BPS {
+            return err!(CrowdfundError::FeeTooHigh);
+        }
+
+        let platform = &mut ctx.accounts.platform;
+        platform.abandon_window = abandon_window;
+        platform.abandon_penalty_bps = abandon_penalty_bps;
+
+        msg!("Abandon policy set: {} seconds, {} bps penalty", abandon_window, abandon_penalty_bps);
+        Ok(())
+    }
+
+
+    pub fn set_shortfall_policy(ctx: Context<UpdatePlatform>, max_shortfall_cover: u64, shortfall_band_bps: u16) -> Result<()> {
+        if shortfall_band_bps > 10_000 {
+            return err!(CrowdfundError::InvalidPercentage);
+        }
+
+        let platform = &mut ctx.accounts.platform;
+        platform.max_shortfall_cover = max_shortfall_cover;
+        platform.shortfall_band_bps = shortfall_band_bps;
+
+        msg!("Shortfall policy set: up to {} lamports within {} bps of goal", max_shortfall_cover, shortfall_band_bps);
+        Ok(())
+    }
+
+
+    pub fn set_clock_drift_tolerance(ctx: Context<UpdatePlatform>, clock_drift_tolerance: i64) -> Result<()> {
+        if !(0..=MAX_CLOCK_DRIFT_TOLERANCE).contains(&clock_drift_tolerance) {
+            return err!(CrowdfundError::InvalidClockDriftTolerance);
+        }
+
+        ctx.accounts.platform.clock_drift_tolerance = clock_drift_tolerance;
+        msg!("Clock drift tolerance set: {} seconds", clock_drift_tolerance);
+        Ok(())
+    }
+
+
+    pub fn set_goal_increment(ctx: Context<UpdatePlatform>, goal_increment: u64) -> Result<()> {
+        if goal_increment == 0 {
+            return err!(CrowdfundError::InvalidGoalIncrement);
+        }
+
+        ctx.accounts.platform.goal_increment = goal_increment;
+        msg!("Goal increment set: {} lamports", goal_increment);
+        Ok(())
+    }
+
+
+    pub fn set_min_age_before_cancel(ctx: Context<UpdatePlatform>, min_age_before_cancel: i64) -> Result<()> {
+        ctx.accounts.platform.min_age_before_cancel = min_age_before_cancel;
+
+        msg!("Min age before cancel set: {} seconds", min_age_before_cancel);
+        Ok(())
+    }
+
+
+    pub fn set_plausibility_bounds(
+        ctx: Context<UpdatePlatform>,
+        min_goal_per_day: u64,
+        max_goal_per_day: u64,
+        enforce: bool,
+    ) -> Result<()> {
+        let platform = &mut ctx.accounts.platform;
+
+        platform.min_goal_per_day = min_goal_per_day;
+        platform.max_goal_per_day = max_goal_per_day;
+        platform.enforce_plausibility = enforce;
+
+        msg!("Plausibility bounds set: {}..{} lamports/day, enforced: {}", min_goal_per_day, max_goal_per_day, enforce);
+        Ok(())
+    }
+
+
+    pub fn is_live(ctx: Context<CampaignLive>) -> Result<bool> {
+        let campaign = &ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+        Ok(!campaign.cancelled
+            && !campaign.claimed
+            && !campaign.has_ended(clock.unix_timestamp)
+            && campaign.raised >= ctx.accounts.platform.min_visible_raise)
+    }
+
+
+    pub fn fund_rebate_pool(ctx: Context<FundRebatePool>, amount: u64) -> Result<()> {
+        let pool = &mut ctx.accounts.rebate_pool;
+
+        if pool.sponsor == Pubkey::default() {
+            pool.campaign = ctx.accounts.campaign.key();
+            pool.sponsor = *ctx.accounts.sponsor.key;
+            pool.bump = ctx.bumps.rebate_pool;
+        }
+
+
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.sponsor.to_account_info(),
+                to: pool.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, amount)?;
+
+        pool.remaining += amount;
+        msg!("Rebate pool funded: {} lamports. Remaining: {}", amount, pool.remaining);
+        Ok(())
+    }
+
+
+    pub fn claim_rebate(ctx: Context<ClaimRebate>) -> Result<()> {
+        let pool = &mut ctx.accounts.rebate_pool;
+        let contribution = &mut ctx.accounts.contribution;
+
+
+        if contribution.rebate_claimed {
+            return err!(CrowdfundError::RebateAlreadyClaimed);
+        }
+        if pool.remaining == 0 {
+            return err!(CrowdfundError::RebatePoolExhausted);
+        }
+
+
+        let rebate = CONTRIBUTION_REBATE.min(pool.remaining);
+        **pool.to_account_info().try_borrow_mut_lamports()? -= rebate;
+        **ctx.accounts.donor.to_account_info().try_borrow_mut_lamports()? += rebate;
+
+        pool.remaining -= rebate;
+        contribution.rebate_claimed = true;
+        msg!("Rebate claimed: {} lamports. Pool remaining: {}", rebate, pool.remaining);
+        Ok(())
+    }
+
+
+    pub fn salvage(ctx: Context<Salvage>, new_goal: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+
+        campaign.roll_if_due(clock.unix_timestamp);
+        if !campaign.has_ended(clock.unix_timestamp) {
+            return err!(CrowdfundError::CampaignNotEnded);
+        }
+        if clock.unix_timestamp >= campaign.deadline + ctx.accounts.platform.salvage_window {
+            return err!(CrowdfundError::SalvageWindowClosed);
+        }
+        if campaign.refunds_started {
+            return err!(CrowdfundError::SalvageWindowClosed);
+        }
+        if campaign.is_goal_met() {
+            return err!(CrowdfundError::GoalAlreadyMet);
+        }
+        if (campaign.raised as u128) * 10_000 < (campaign.goal as u128) * SALVAGE_MIN_RAISED_BPS as u128 {
+            return err!(CrowdfundError::NotNearMiss);
+        }
+        if new_goal > campaign.raised {
+            return err!(CrowdfundError::SalvageGoalTooHigh);
+        }
+        campaign.assert_unlocked(clock.unix_timestamp)?;
+        campaign.assert_state_log(&ctx.accounts.state_log)?;
+
+        let delay = ctx.accounts.platform.goal_reduction_delay;
+        if delay > 0 {
+            campaign.pending_goal = new_goal;
+            campaign.pending_goal_at = clock.unix_timestamp + delay;
+
+            msg!("Goal reduction to {} lamports announced, effective at {}", new_goal, campaign.pending_goal_at);
+            return Ok(());
+        }
+
+        let old_goal = campaign.goal;
+        campaign.goal = new_goal;
+        campaign.refresh_filter_key();
+        if let Some(log) = &mut ctx.accounts.state_log {
+            log.record(CampaignState::Succeeded, clock.unix_timestamp, ctx.accounts.creator.key());
+        }
+
+        msg!("Campaign salvaged! Goal reduced from {} to {} lamports", old_goal, new_goal);
+        Ok(())
+    }
+
+
+    pub fn apply_goal_reduction(ctx: Context<ApplyGoalReduction>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+
+        if campaign.pending_goal_at == 0 {
+            return err!(CrowdfundError::NoPendingGoalReduction);
+        }
+        if clock.unix_timestamp < campaign.pending_goal_at {
+            return err!(CrowdfundError::GoalReductionTimelocked);
+        }
+        if campaign.refunds_started {
+            return err!(CrowdfundError::SalvageWindowClosed);
+        }
+        if campaign.pending_goal > campaign.raised {
+            return err!(CrowdfundError::SalvageGoalTooHigh);
+        }
+        campaign.assert_unlocked(clock.unix_timestamp)?;
+        campaign.assert_state_log(&ctx.accounts.state_log)?;
+
+        let old_goal = campaign.goal;
+        campaign.goal = campaign.pending_goal;
+        campaign.refresh_filter_key();
+        if let Some(log) = &mut ctx.accounts.state_log {
+            log.record(CampaignState::Succeeded, clock.unix_timestamp, ctx.accounts.creator.key());
+        }
+        campaign.pending_goal = 0;
+        campaign.pending_goal_at = 0;
+
+        msg!("Campaign salvaged! Goal reduced from {} to {} lamports", old_goal, campaign.goal);
+        Ok(())
+    }
+
+
+    pub fn campaign_exists(ctx: Context<CampaignExists>, _creator: Pubkey, _name: String) -> Result<bool> {
+        let campaign_info = &ctx.accounts.campaign;
+
+        if campaign_info.owner != ctx.program_id || campaign_info.data_len() < 8 {
+            return Ok(false);
+        }
+
+        let data = campaign_info.try_borrow_data()?;
+        Ok(&data[..8] == Campaign::DISCRIMINATOR)
+    }
+
+
+    pub fn has_contributed(ctx: Context<HasContributed>, _campaign: Pubkey, _donor: Pubkey) -> Result<bool> {
+        let contribution_info = &ctx.accounts.contribution;
+
+        if contribution_info.owner != ctx.program_id || contribution_info.data_len() < 8 {
+            return Ok(false);
+        }
+
+        let data = contribution_info.try_borrow_data()?;
+        Ok(&data[..8] == Contribution::DISCRIMINATOR)
+    }
+
+
+    pub fn get_campaign_info(ctx: Context<CampaignView>) -> Result<CampaignInfo> {
+        let campaign = &ctx.accounts.campaign;
+
+        Ok(CampaignInfo {
+            creator: campaign.creator,
+            goal: campaign.goal,
+            raised: campaign.raised,
+            deadline: campaign.deadline,
+            claimed: campaign.claimed,
+            contributors: campaign.contributors,
+            contribution_init_mode: campaign.contribution_init_mode,
+            goal_met: campaign.is_goal_met(),
+            counted_contributors: campaign.counted_contributors,
+        })
+    }
+
+
+    // Rank is computed against the contributions passed in remaining_accounts,
+    // so it is only exact when the caller supplies every backer's contribution.
+    pub fn get_donor_rank<'info>(ctx: Context<'_, '_, 'info, 'info, DonorRank<'info>>) -> Result<u32> {
+        let contribution = &ctx.accounts.contribution;
+        let campaign_key = ctx.accounts.campaign.key();
+
+        let mut seen: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut rank = 1u32;
+        for info in ctx.remaining_accounts.iter() {
+            if seen.contains(info.key) {
+                return err!(CrowdfundError::InvalidRemainingAccounts);
+            }
+            seen.push(info.key());
+            if info.key() == contribution.key() {
+                continue;
+            }
+            let other: Account<Contribution> = Account::try_from(info)?;
+            if other.campaign != campaign_key {
+                return err!(CrowdfundError::InvalidContributionAccount);
+            }
+            if other.amount > contribution.amount {
+                rank += 1;
+            }
+        }
+
+        Ok(rank)
+    }
+
+
+    pub fn top_up_vault_rent(ctx: Context<TopUpVaultRent>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        if campaign.vault_rent_reserved {
+            return err!(CrowdfundError::VaultRentAlreadyReserved);
+        }
+
+
+        let reserve = Rent::get()?.minimum_balance(0);
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, reserve)?;
+
+        campaign.vault_rent_reserved = true;
+        msg!("Vault rent reserved: {} lamports", reserve);
+        Ok(())
+    }
+
+
+    pub fn crank_cleanup(ctx: Context<CrankCleanup>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let vault = &ctx.accounts.vault;
+        let clock = Clock::get()?;
+
+
+        campaign.roll_if_due(clock.unix_timestamp);
+        if campaign.contributors != 0 {
+            return err!(CrowdfundError::ContributionsRemain);
+        }
+        campaign.assert_no_held_funds()?;
+        if !campaign.claimed && !campaign.cancelled && !campaign.has_ended(clock.unix_timestamp) {
+            return err!(CrowdfundError::CampaignNotEnded);
+        }
+        if !campaign.claimed && !campaign.cancelled && campaign.is_payable() {
+            return err!(CrowdfundError::CampaignNotTerminal);
+        }
+        campaign.assert_state_log(&ctx.accounts.state_log)?;
+
+
+        let dust = vault.lamports();
+        if dust > 0 {
+            let campaign_key = campaign.key();
+            let seeds = &[
+                b"vault",
+                campaign_key.as_ref(),
+                &[ctx.bumps.vault],
+            ];
+            let signer_seeds = &[&seeds[..]];
+
+            let cpi_context = CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: vault.to_account_info(),
+                    to: ctx.accounts.creator.to_account_info(),
+                },
+                signer_seeds,
+            );
+            system_program::transfer(cpi_context, dust)?;
+        }
+
+
+        let campaign_info = campaign.to_account_info();
+        **campaign_info.try_borrow_mut_lamports()? -= KEEPER_REWARD;
+        **ctx.accounts.keeper.to_account_info().try_borrow_mut_lamports()? += KEEPER_REWARD;
+
+        campaign.finalize(&mut ctx.accounts.platform_stats);
+        if let Some(log) = &mut ctx.accounts.state_log {
+            log.record(CampaignState::Closed, clock.unix_timestamp, ctx.accounts.keeper.key());
+        }
+        msg!("Campaign cleaned up! Swept {} lamports, keeper reward {}", dust, KEEPER_REWARD);
+        Ok(())
+    }
+
+
+    pub fn set_auto_refund(ctx: Context<SetAutoRefund>, opt_in: bool) -> Result<()> {
+        let clock = Clock::get()?;
+
+        if ctx.accounts.campaign.has_ended(clock.unix_timestamp) {
+            return err!(CrowdfundError::CampaignEnded);
+        }
+
+        ctx.accounts.contribution.auto_refund = opt_in;
+        msg!("Auto refund opt-in: {}", opt_in);
+        Ok(())
+    }
+
+
+    pub fn process_auto_refunds<'info>(ctx: Context<'_, '_, 'info, 'info, ProcessAutoRefunds<'info>>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let campaign_key = campaign.key();
+        let clock = Clock::get()?;
+
+
+        campaign.roll_if_due(clock.unix_timestamp);
+        campaign.assert_refundable(clock.unix_timestamp)?;
+        campaign.assert_state_log(&ctx.accounts.state_log)?;
+        if ctx.remaining_accounts.len() % 3 != 0 {
+            return err!(CrowdfundError::InvalidRemainingAccounts);
+        }
+
+
+        let mut processed = 0u32;
+        for accounts in ctx.remaining_accounts.chunks(3) {
+            let (contribution_info, donor_info, loyalty_info) = (&accounts[0], &accounts[1], &accounts[2]);
+            if donor_info.key() == ctx.accounts.vault.key() {
+                return err!(CrowdfundError::InvalidRefundRecipient);
+            }
+
+            let contribution: Account<Contribution> = Account::try_from(contribution_info)?;
+            let (expected, _) = Pubkey::find_program_address(
+                &[b"contribution", campaign_key.as_ref(), donor_info.key.as_ref()],
+                ctx.program_id,
+            );
+            if contribution_info.key() != expected || contribution.donor != donor_info.key() {
+                return err!(CrowdfundError::InvalidContributionAccount);
+            }
+            // A cancelled campaign is refunded to everyone, opted in or not.
+            if !contribution.auto_refund && !campaign.cancelled {
+                return err!(CrowdfundError::AutoRefundNotEnabled);
+            }
+
+
+            let amount = contribution.amount;
+            let (payout, fee) = refund_split(
+                campaign.take_refund_share(amount),
+                ctx.accounts.platform.failure_fee_bps,
+                campaign.refundable_balance(ctx.accounts.vault.lamports())?,
+                campaign.contributors == 1,
+            );
+            if fee > 0 {
+                transfer_from_vault(
+                    &ctx.accounts.system_program,
+                    &ctx.accounts.vault,
+                    ctx.bumps.vault,
+                    campaign_key,
+                    ctx.accounts.treasury.to_account_info(),
+                    fee,
+                )?;
+            }
+            transfer_from_vault(
+                &ctx.accounts.system_program,
+                &ctx.accounts.vault,
+                ctx.bumps.vault,
+                campaign_key,
+                donor_info.clone(),
+                payout,
+            )?;
+
+            if ctx.accounts.platform.loyalty_clawback {
+                let mut loyalty: Account<LoyaltyAccount> = Account::try_from(loyalty_info)?;
+                if loyalty.donor != donor_info.key() {
+                    return err!(CrowdfundError::InvalidRemainingAccounts);
+                }
+                loyalty.points = loyalty.points.saturating_sub(amount / LOYALTY_LAMPORTS_PER_POINT);
+                loyalty.exit(ctx.program_id)?;
+            }
+
+            if !campaign.refunds_started {
+                if let Some(log) = &mut ctx.accounts.state_log {
+                    log.record(CampaignState::Failed, clock.unix_timestamp, ctx.accounts.keeper.key());
+                }
+            }
+            campaign.raised -= amount;
+            campaign.contributors -= 1;
+            campaign.track_counted(amount, 0);
+            campaign.refunds_started = true;
+            campaign.refresh_filter_key();
+            ctx.accounts.platform_stats.total_locked = ctx.accounts.platform_stats.total_locked.saturating_sub(amount);
+            contribution.close(donor_info.clone())?;
+            processed += 1;
+        }
+
+        msg!("Auto refunds processed: {}", processed);
+        Ok(())
+    }
+
+
+    pub fn derive_addresses(
+        ctx: Context<DeriveAddresses>,
+        creator: Pubkey,
+        name: String,
+        donor: Pubkey,
+    ) -> Result<DerivedAddresses> {
+        let (campaign, campaign_bump) = Pubkey::find_program_address(
+            &[b"campaign", creator.as_ref(), name.as_bytes()],
+            ctx.program_id,
+        );
+        let (vault, vault_bump) = Pubkey::find_program_address(
+            &[b"vault", campaign.as_ref()],
+            ctx.program_id,
+        );
+        let (contribution, contribution_bump) = Pubkey::find_program_address(
+            &[b"contribution", campaign.as_ref(), donor.as_ref()],
+            ctx.program_id,
+        );
+
+        Ok(DerivedAddresses {
+            campaign,
+            campaign_bump,
+            vault,
+            vault_bump,
+            contribution,
+            contribution_bump,
+        })
+    }
+
+
+    pub fn set_hard_cap(ctx: Context<UpdateCampaign>, hard_cap: bool) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        if campaign.raised != 0 {
+            return err!(CrowdfundError::CampaignHasFunds);
+        }
+
+        campaign.hard_cap = hard_cap;
+        msg!("Hard cap: {}", hard_cap);
+        Ok(())
+    }
+
+
+    pub fn append_description_chunk(ctx: Context<AppendDescriptionChunk>, content: String) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let chunk = &mut ctx.accounts.chunk;
+
+
+        if campaign.description_chunks >= MAX_DESCRIPTION_CHUNKS {
+            return err!(CrowdfundError::TooManyDescriptionChunks);
+        }
+        if content.is_empty() || content.len() > MAX_CHUNK_LEN {
+            return err!(CrowdfundError::InvalidChunkLength);
+        }
+
+        chunk.campaign = campaign.key();
+        chunk.index = campaign.description_chunks;
+        chunk.content = content;
+        chunk.bump = ctx.bumps.chunk;
+        campaign.description_chunks += 1;
+
+        msg!("Description chunk {} appended", chunk.index);
+        Ok(())
+    }
+
+
+    pub fn set_count_threshold(ctx: Context<UpdateCampaign>, count_threshold: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        if campaign.raised != 0 {
+            return err!(CrowdfundError::CampaignHasFunds);
+        }
+
+        campaign.count_threshold = count_threshold;
+        msg!("Contributors counted from {} lamports", count_threshold);
+        Ok(())
+    }
+
+
+    pub fn set_soft_close_window(ctx: Context<UpdateCampaign>, soft_close_window: i64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        if campaign.raised != 0 {
+            return err!(CrowdfundError::CampaignHasFunds);
+        }
+        if soft_close_window < 0 || soft_close_window > campaign.duration {
+            return err!(CrowdfundError::InvalidSoftCloseWindow);
+        }
+
+        campaign.soft_close_window = soft_close_window;
+        msg!("Contributions in the final {} seconds are flagged late", soft_close_window);
+        Ok(())
+    }
+
+
+    pub fn set_lock_until(ctx: Context<UpdateCampaign>, lock_until: i64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        if campaign.raised != 0 {
+            return err!(CrowdfundError::CampaignHasFunds);
+        }
+        if lock_until > campaign.deadline + MAX_LOCK_AFTER_DEADLINE {
+            return err!(CrowdfundError::LockTooLong);
+        }
+
+        campaign.lock_until = lock_until;
+        msg!("Contributions locked until {}", lock_until);
+        Ok(())
+    }
+
+
+    pub fn set_tranche_schedule(ctx: Context<UpdateCampaign>, tranche_count: u8, tranche_interval: i64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        if campaign.raised != 0 {
+            return err!(CrowdfundError::CampaignHasFunds);
+        }
+        if tranche_count > 1 && tranche_interval <= 0 {
+            return err!(CrowdfundError::InvalidTrancheSchedule);
+        }
+
+        campaign.tranche_count = tranche_count;
+        campaign.tranche_interval = tranche_interval;
+        msg!("Payout in {} tranches, {} seconds apart", tranche_count.max(1), tranche_interval);
+        Ok(())
+    }
+
+
+    pub fn set_funding_mode(ctx: Context<UpdateCampaign>, mode: u8) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        if mode > FUNDING_MODE_KEEP_WHAT_YOU_RAISE {
+            return err!(CrowdfundError::InvalidFundingMode);
+        }
+        if campaign.raised != 0 {
+            return err!(CrowdfundError::CampaignHasFunds);
+        }
+
+        campaign.funding_mode = mode;
+        msg!("Funding mode: {}", mode);
+        Ok(())
+    }
+
+
+    pub fn set_contribution_cap_schedule(
+        ctx: Context<UpdateCampaign>,
+        base_cap: u64,
+        final_window_bps: u16,
+        final_cap_bps: u16,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        if final_window_bps > 10_000 || final_cap_bps > 10_000 {
+            return err!(CrowdfundError::InvalidPercentage);
+        }
+        if campaign.raised != 0 {
+            return err!(CrowdfundError::CampaignHasFunds);
+        }
+
+        campaign.base_contribution_cap = base_cap;
+        campaign.final_window_bps = final_window_bps;
+        campaign.final_cap_bps = final_cap_bps;
+
+        msg!("Contribution cap {} lamports, {} bps of it in the final {} bps", base_cap, final_cap_bps, final_window_bps);
+        Ok(())
+    }
+
+
+    pub fn set_max_contribution_bps_of_goal(ctx: Context<UpdateCampaign>, max_bps: u16) -> Result<()> {
+        if max_bps > 10_000 {
+            return err!(CrowdfundError::InvalidPercentage);
+        }
+
+        ctx.accounts.campaign.max_contribution_bps_of_goal = max_bps;
+        msg!("Max single contribution: {} bps of goal", max_bps);
+        Ok(())
+    }
+
+
+    pub fn set_block_self_delegation(ctx: Context<UpdateCampaign>, block: bool) -> Result<()> {
+        ctx.accounts.campaign.block_self_delegation = block;
+        msg!("Block self delegation: {}", block);
+        Ok(())
+    }
+
+
+    pub fn deposit_reward_pool(ctx: Context<DepositRewardPool>, amount: u64) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let pool = &mut ctx.accounts.reward_pool;
+
+        if !campaign.is_goal_met() {
+            return err!(CrowdfundError::GoalNotMet);
+        }
+
+
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.creator.to_account_info(),
+                to: pool.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, amount)?;
+
+        pool.campaign = campaign.key();
+        pool.bump = ctx.bumps.reward_pool;
+        pool.total += amount;
+        msg!("Reward pool deposit: {} lamports. Total: {}", amount, pool.total);
+        Ok(())
+    }
+
+
+    pub fn claim_bonus(ctx: Context<ClaimBonus>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let pool = &mut ctx.accounts.reward_pool;
+        let contribution = &mut ctx.accounts.contribution;
+        let clock = Clock::get()?;
+
+
+        if !campaign.has_ended(clock.unix_timestamp) {
+            return err!(CrowdfundError::CampaignNotEnded);
+        }
+        if !campaign.is_goal_met() {
+            return err!(CrowdfundError::CampaignNotSuccessful);
+        }
+
+        let entitled = (contribution.amount as u128 * pool.total as u128 / campaign.raised as u128) as u64;
+        let bonus = entitled.saturating_sub(contribution.bonus_claimed);
+        if bonus == 0 {
+            return err!(CrowdfundError::BonusAlreadyClaimed);
+        }
+
+
+        **pool.to_account_info().try_borrow_mut_lamports()? -= bonus;
+        **ctx.accounts.donor.to_account_info().try_borrow_mut_lamports()? += bonus;
+
+        pool.distributed += bonus;
+        contribution.bonus_claimed += bonus;
+        msg!("Bonus claimed: {} lamports", bonus);
+        Ok(())
+    }
+
+
+    pub fn get_error_category(_ctx: Context<ErrorCategoryView>, code: u32) -> Result<ErrorCategory> {
+        Ok(ErrorCategory::of(code))
+    }
+
+
+    pub fn set_max_rolls(ctx: Context<UpdateCampaign>, max_rolls: u8) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+        if campaign.has_ended(clock.unix_timestamp) {
+            return err!(CrowdfundError::CampaignEnded);
+        }
+
+        campaign.max_rolls = max_rolls;
+        msg!("Max deadline rolls: {}", max_rolls);
+        Ok(())
+    }
+
+
+    pub fn roll_deadline(ctx: Context<RollDeadline>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+        if !campaign.roll_if_due(clock.unix_timestamp) {
+            return err!(CrowdfundError::DeadlineNotRollable);
+        }
+
+        msg!("Deadline rolled to {} ({} of {} rolls)", campaign.deadline, campaign.roll_count, campaign.max_rolls);
+        Ok(())
+    }
+
+
+    // Closing frees the `creator + name` address: a later create_campaign with the
+    // same name initializes a zeroed account, and the vault is swept so no lamports
+    // carry over. Accounts seeded by the campaign address (description chunks,
+    // comments, pools, referral stats, the state log) are not closed, but their
+    // seeds include the generation, so the recreated campaign derives fresh ones.
+    pub fn close_campaign(ctx: Context<CloseCampaign>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+
+        campaign.roll_if_due(clock.unix_timestamp);
+        if campaign.contributors != 0 {
+            return err!(CrowdfundError::ContributionsRemain);
+        }
+        campaign.assert_no_held_funds()?;
+        let failed = campaign.cancelled || (campaign.has_ended(clock.unix_timestamp) && !campaign.is_payable());
+        if !campaign.claimed && !failed && campaign.raised != 0 {
+            return err!(CrowdfundError::CampaignNotTerminal);
+        }
+        campaign.assert_state_log(&ctx.accounts.state_log)?;
+
+
+        let dust = ctx.accounts.vault.lamports();
+        if dust > 0 {
+            transfer_from_vault(
+                &ctx.accounts.system_program,
+                &ctx.accounts.vault,
+                ctx.bumps.vault,
+                campaign.key(),
+                ctx.accounts.creator.to_account_info(),
+                dust,
+            )?;
+        }
+
+        campaign.finalize(&mut ctx.accounts.platform_stats);
+        if let Some(log) = &mut ctx.accounts.state_log {
+            log.record(CampaignState::Closed, clock.unix_timestamp, ctx.accounts.creator.key());
+        }
+        msg!("Campaign closed! Name: {}, swept {} lamports", campaign.name, dust);
+        Ok(())
+    }
+
+
+    // The creator funds the shared pool once so micro donors pay no rent.
+    pub fn open_micro_pool(ctx: Context<OpenMicroPool>) -> Result<()> {
+        let pool = &mut ctx.accounts.micro_pool;
+        pool.campaign = ctx.accounts.campaign.key();
+        pool.bump = ctx.bumps.micro_pool;
+
+        msg!("Micro pool opened for campaign {}", pool.campaign);
+        Ok(())
+    }
+
+
+    pub fn close_micro_pool(ctx: Context<CloseMicroPool>) -> Result<()> {
+        if !ctx.accounts.campaign.claimed && !ctx.accounts.micro_pool.entries.is_empty() {
+            return err!(CrowdfundError::MicroPoolNotEmpty);
+        }
+
+        msg!("Micro pool closed, rent returned to the creator");
+        Ok(())
+    }
+
+
+    pub fn micro_contribute(ctx: Context<MicroContribute>, amount: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let pool = &mut ctx.accounts.micro_pool;
+        let donor = ctx.accounts.donor.key();
+        let clock = Clock::get()?;
+
+
+        if amount > MAX_MICRO_CONTRIBUTION {
+            return err!(CrowdfundError::MicroContributionTooLarge);
+        }
+        prepare_contribution(
+            campaign,
+            &ctx.accounts.platform,
+            &ctx.accounts.platform_stats,
+            &donor,
+            amount,
+            clock.unix_timestamp,
+        )?;
+        campaign.assert_state_log(&ctx.accounts.state_log)?;
+        if ctx.accounts.donor.lamports() < amount {
+            return err!(CrowdfundError::InsufficientDonorFunds);
+        }
+
+        let (new_backer, previous) = match pool.entries.iter_mut().find(|entry| entry.donor == donor) {
+            Some(entry) => {
+                entry.amount += amount;
+                (false, entry.amount - amount)
+            }
+            None => {
+                if pool.entries.len() >= MAX_MICRO_ENTRIES {
+                    return err!(CrowdfundError::MicroPoolFull);
+                }
+                pool.entries.push(MicroEntry { donor, amount });
+                (true, 0)
+            }
+        };
+
+
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.donor.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, amount)?;
+
+        credit_campaign(
+            campaign,
+            &ctx.accounts.vault,
+            &mut ctx.accounts.platform_stats,
+            ctx.accounts.state_log.as_mut(),
+            ctx.accounts.histogram.as_mut(),
+            donor,
+            new_backer,
+            previous,
+            amount,
+            clock.unix_timestamp,
+        )?;
+
+        msg!("Micro contribution: {} lamports. Pool entries: {}", amount, pool.entries.len());
+        Ok(())
+    }
+
+
+    pub fn micro_refund(ctx: Context<MicroRefund>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let pool = &mut ctx.accounts.micro_pool;
+        let donor = ctx.accounts.donor.key();
+        let clock = Clock::get()?;
+
+
+        campaign.roll_if_due(clock.unix_timestamp);
+        campaign.assert_refundable(clock.unix_timestamp)?;
+        campaign.assert_state_log(&ctx.accounts.state_log)?;
+        let index = match pool.entries.iter().position(|entry| entry.donor == donor) {
+            Some(index) => index,
+            None => return err!(CrowdfundError::NoMicroContribution),
+        };
+
+
+        let amount = pool.entries.swap_remove(index).amount;
+        let (payout, _) = refund_split(
+            campaign.take_refund_share(amount),
+            0,
+            campaign.refundable_balance(ctx.accounts.vault.lamports())?,
+            campaign.contributors == 1,
+        );
+        transfer_from_vault(
+            &ctx.accounts.system_program,
+            &ctx.accounts.vault,
+            ctx.bumps.vault,
+            campaign.key(),
+            ctx.accounts.donor.to_account_info(),
+            payout,
+        )?;
+
+        if !campaign.refunds_started {
+            if let Some(log) = &mut ctx.accounts.state_log {
+                log.record(CampaignState::Failed, clock.unix_timestamp, donor);
+            }
+        }
+        campaign.raised -= amount;
+        campaign.contributors -= 1;
+        campaign.track_counted(amount, 0);
+        campaign.refunds_started = true;
+        campaign.refresh_filter_key();
+        ctx.accounts.platform_stats.total_locked = ctx.accounts.platform_stats.total_locked.saturating_sub(amount);
+
+        msg!("Micro refund: {} lamports", payout);
+        Ok(())
+    }
+
+
+    pub fn report_campaign(ctx: Context<ReportCampaign>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let report = &mut ctx.accounts.report;
+
+        report.campaign = campaign.key();
+        report.reporter = *ctx.accounts.reporter.key;
+        report.bump = ctx.bumps.report;
+        campaign.report_count += 1;
+
+        msg!("Campaign reported. Open reports: {}", campaign.report_count);
+        Ok(())
+    }
+
+
+    pub fn clear_reports(ctx: Context<ModerateCampaign>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        let cleared = campaign.report_count;
+        campaign.report_count = 0;
+
+        msg!("Cleared {} reports", cleared);
+        Ok(())
+    }
+
+
+    pub fn set_category(ctx: Context<UpdateCampaign>, category: u8) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        campaign.category = category;
+        campaign.refresh_filter_key();
+
+        msg!("Category set: {}", category);
+        Ok(())
+    }
+
+
+    pub fn set_listed(ctx: Context<UpdateCampaign>, listed: bool) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        campaign.listed = listed;
+        campaign.refresh_filter_key();
+
+        msg!("Listed: {}", listed);
+        Ok(())
+    }
+
+
+    pub fn set_verified(ctx: Context<ModerateCampaign>, verified: bool) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        campaign.verified = verified;
+        campaign.refresh_filter_key();
+
+        msg!("Verified: {}", verified);
+        Ok(())
+    }
+
+
+    pub fn freeze_contributions(ctx: Context<UpdateCampaign>) -> Result<()> {
+        ctx.accounts.campaign.contributions_frozen = true;
+        msg!("Contributions frozen");
+        Ok(())
+    }
+
+
+    pub fn unfreeze_contributions(ctx: Context<UpdateCampaign>) -> Result<()> {
+        ctx.accounts.campaign.contributions_frozen = false;
+        msg!("Contributions unfrozen");
+        Ok(())
+    }
+
+
+    pub fn set_charity_fallback(ctx: Context<UpdateCampaign>, charity_fallback: Pubkey) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+        campaign.assert_active(clock.unix_timestamp)?;
+        let (vault, _) = Pubkey::find_program_address(&[b"vault", campaign.key().as_ref()], ctx.program_id);
+        if charity_fallback == vault {
+            return err!(CrowdfundError::InvalidRefundRecipient);
+        }
+
+        campaign.charity_fallback = charity_fallback;
+        msg!("Charity fallback set: {}", charity_fallback);
+        Ok(())
+    }
+
+
+    pub fn route_to_charity(ctx: Context<RouteToCharity>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let amount = ctx.accounts.contribution.amount;
+        let clock = Clock::get()?;
+
+
+        if campaign.charity_fallback == Pubkey::default() {
+            return err!(CrowdfundError::NoCharityFallback);
+        }
+        if clock.unix_timestamp < campaign.deadline + ctx.accounts.platform.charity_timeout {
+            return err!(CrowdfundError::CharityTimeoutNotElapsed);
+        }
+        campaign.assert_refundable(clock.unix_timestamp)?;
+        campaign.assert_state_log(&ctx.accounts.state_log)?;
+
+
+        let (payout, _) = refund_split(
+            campaign.take_refund_share(amount),
+            0,
+            campaign.refundable_balance(ctx.accounts.vault.lamports())?,
+            campaign.contributors == 1,
+        );
+        transfer_from_vault(
+            &ctx.accounts.system_program,
+            &ctx.accounts.vault,
+            ctx.bumps.vault,
+            campaign.key(),
+            ctx.accounts.charity.to_account_info(),
+            payout,
+        )?;
+
+        if !campaign.refunds_started {
+            if let Some(log) = &mut ctx.accounts.state_log {
+                log.record(CampaignState::Failed, clock.unix_timestamp, ctx.accounts.keeper.key());
+            }
+        }
+        campaign.raised -= amount;
+        campaign.contributors -= 1;
+        campaign.track_counted(amount, 0);
+        campaign.refunds_started = true;
+        campaign.refresh_filter_key();
+        ctx.accounts.platform_stats.total_locked = ctx.accounts.platform_stats.total_locked.saturating_sub(amount);
+
+        msg!("Unclaimed contribution of {} lamports routed to charity", payout);
+        Ok(())
+    }
+
+
+    pub fn set_payout_address(ctx: Context<UpdateCampaign>, payout_address: Pubkey) -> Result<()> {
+        let (vault, _) = Pubkey::find_program_address(&[b"vault", ctx.accounts.campaign.key().as_ref()], ctx.program_id);
+        if payout_address == vault {
+            return err!(CrowdfundError::InvalidRefundRecipient);
+        }
+
+        ctx.accounts.campaign.payout_address = payout_address;
+        msg!("Payout address set: {}", payout_address);
+        Ok(())
+    }
+
+
+    pub fn finalize_abandoned(ctx: Context<FinalizeAbandoned>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let platform = &ctx.accounts.platform;
+        let clock = Clock::get()?;
+
+
+        if campaign.cancelled {
+            return err!(CrowdfundError::CampaignCancelled);
+        }
+        if !campaign.is_payable() {
+            return err!(CrowdfundError::CampaignNotSuccessful);
+        }
+        if campaign.claimed {
+            return err!(CrowdfundError::AlreadyClaimed);
+        }
+        if clock.unix_timestamp < campaign.deadline + platform.abandon_window {
+            return err!(CrowdfundError::AbandonWindowNotElapsed);
+        }
+        campaign.assert_state_log(&ctx.accounts.state_log)?;
+
+
+        let (payout, fee) = settle_payout(
+            campaign,
+            platform,
+            &ctx.accounts.system_program,
+            &ctx.accounts.vault,
+            ctx.bumps.vault,
+            ctx.accounts.payout.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            platform.success_fee_bps + platform.abandon_penalty_bps,
+            ctx.accounts.approver.as_ref(),
+            ctx.accounts.first_contributor.as_ref(),
+            clock.unix_timestamp,
+        )?;
+
+        if let Some(log) = &mut ctx.accounts.state_log {
+            log.record(CampaignState::Claimed, clock.unix_timestamp, ctx.accounts.authority.key());
+        }
+        let platform_stats = &mut ctx.accounts.platform_stats;
+        platform_stats.total_locked = platform_stats.total_locked.saturating_sub(campaign.raised);
+        campaign.finalize(platform_stats);
+        msg!("Abandoned campaign finalized: {} lamports to {} (fee and penalty: {})", payout, ctx.accounts.payout.key(), fee);
+        Ok(())
+    }
+
+
+    pub fn cancel_campaign(ctx: Context<CancelCampaign>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+
+        campaign.assert_active(clock.unix_timestamp)?;
+        if clock.unix_timestamp < campaign.created_at + ctx.accounts.platform.min_age_before_cancel {
+            return err!(CrowdfundError::CancelTooEarly);
+        }
+        if campaign.approver_required {
+            match &ctx.accounts.approver {
+                Some(approver) if approver.key() == campaign.approver => {}
+                _ => return err!(CrowdfundError::ApproverSignatureRequired),
+            }
+        }
+        campaign.assert_state_log(&ctx.accounts.state_log)?;
+
+        campaign.cancelled = true;
+        campaign.refresh_filter_key();
+        campaign.finalize(&mut ctx.accounts.platform_stats);
+        if let Some(log) = &mut ctx.accounts.state_log {
+            log.record(CampaignState::Cancelled, clock.unix_timestamp, ctx.accounts.creator.key());
+        }
+
+        msg!("Campaign cancelled! Name: {}", campaign.name);
+        Ok(())
+    }
+
+
+    pub fn deposit_first_backer_incentive(
+        ctx: Context<DepositFirstBackerIncentive>,
+        amount: u64,
+        block_creator_first: bool,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+        campaign.assert_active(clock.unix_timestamp)?;
+
+
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.creator.to_account_info(),
+                to: campaign.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, amount)?;
+
+        campaign.first_backer_incentive += amount;
+        campaign.block_creator_first = block_creator_first;
+        msg!("First backer incentive: {} lamports", campaign.first_backer_incentive);
+        Ok(())
+    }
+
+
+    pub fn set_image_hash(ctx: Context<UpdateCampaign>, hash: [u8; 32]) -> Result<()> {
+        ctx.accounts.campaign.image_hash = hash;
+        msg!("Image hash set");
+        Ok(())
+    }
+
+
+    pub fn fund_creator_match(ctx: Context<FundCreatorMatch>, amount: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+        campaign.assert_active(clock.unix_timestamp)?;
+
+
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.creator.to_account_info(),
+                to: campaign.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, amount)?;
+
+        campaign.creator_match_pool += amount;
+        msg!("Creator match pool funded: {} lamports. Remaining: {}", amount, campaign.creator_match_pool);
+        Ok(())
+    }
+
+
+    pub fn init_state_log(ctx: Context<InitStateLog>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let log = &mut ctx.accounts.state_log;
+
+
+        log.campaign = campaign.key();
+        log.bump = ctx.bumps.state_log;
+        log.record(CampaignState::Created, campaign.created_at, campaign.creator);
+        campaign.state_log_enabled = true;
+        msg!("State log opened for campaign: {}", campaign.name);
+        Ok(())
+    }
+
+
+    pub fn cover_shortfall(ctx: Context<CoverShortfall>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let platform = &ctx.accounts.platform;
+        let treasury = &ctx.accounts.treasury;
+        let clock = Clock::get()?;
+
+
+        if !campaign.has_ended(clock.unix_timestamp) {
+            return err!(CrowdfundError::CampaignNotEnded);
+        }
+        if campaign.cancelled {
+            return err!(CrowdfundError::CampaignCancelled);
+        }
+        if campaign.is_goal_met() || campaign.claimed {
+            return err!(CrowdfundError::GoalAlreadyMet);
+        }
+        if campaign.refunds_started {
+            return err!(CrowdfundError::ShortfallNotCoverable);
+        }
+        let shortfall = campaign.goal - campaign.raised;
+        let band = fee_for(campaign.goal, platform.shortfall_band_bps);
+        if shortfall > platform.max_shortfall_cover || shortfall > band {
+            return err!(CrowdfundError::ShortfallNotCoverable);
+        }
+        let rent = Rent::get()?;
+        if treasury.lamports() < shortfall + rent.minimum_balance(0) {
+            return err!(CrowdfundError::InsufficientTreasury);
+        }
+        campaign.assert_state_log(&ctx.accounts.state_log)?;
+
+
+        let seeds = &[b"treasury".as_ref(), &[ctx.bumps.treasury]];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_context = CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: treasury.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        system_program::transfer(cpi_context, shortfall)?;
+
+        campaign.raised += shortfall;
+        campaign.refresh_filter_key();
+        ctx.accounts.platform_stats.total_locked += shortfall;
+        if let Some(log) = &mut ctx.accounts.state_log {
+            log.record(CampaignState::Succeeded, clock.unix_timestamp, ctx.accounts.authority.key());
+        }
+        msg!("Shortfall covered: {} lamports. Total Raised: {}", shortfall, campaign.raised);
+        Ok(())
+    }
+
+
+    pub fn expire_empty_batch<'info>(ctx: Context<'_, '_, 'info, 'info, ExpireEmptyBatch<'info>>) -> Result<()> {
+        let clock = Clock::get()?;
+
+
+        if ctx.remaining_accounts.len() % 3 != 0 {
+            return err!(CrowdfundError::InvalidRemainingAccounts);
+        }
+
+
+        let mut closed = 0u32;
+        for accounts in ctx.remaining_accounts.chunks(3) {
+            let (campaign_info, vault_info, creator_info) = (&accounts[0], &accounts[1], &accounts[2]);
+
+            let mut campaign: Account<Campaign> = Account::try_from(campaign_info)?;
+            let (expected_vault, vault_bump) =
+                Pubkey::find_program_address(&[b"vault", campaign_info.key.as_ref()], ctx.program_id);
+            if vault_info.key() != expected_vault || creator_info.key() != campaign.creator {
+                return err!(CrowdfundError::InvalidRemainingAccounts);
+            }
+            // An empty campaign with rolls left has not expired yet, and one
+            // keeping a state log is left to crank_cleanup, which records the close.
+            campaign.roll_if_due(clock.unix_timestamp);
+            if campaign.state_log_enabled
+                || campaign.raised != 0
+                || campaign.contributors != 0
+                || campaign.conditional_match_outstanding != 0
+                || !campaign.has_ended(clock.unix_timestamp)
+            {
+                continue;
+            }
+
+
+            let vault: SystemAccount = SystemAccount::try_from(vault_info)?;
+            let dust = vault.lamports();
+            if dust > 0 {
+                transfer_from_vault(
+                    &ctx.accounts.system_program,
+                    &vault,
+                    vault_bump,
+                    campaign.key(),
+                    creator_info.clone(),
+                    dust,
+                )?;
+            }
+
+            **campaign_info.try_borrow_mut_lamports()? -= KEEPER_REWARD;
+            **ctx.accounts.keeper.to_account_info().try_borrow_mut_lamports()? += KEEPER_REWARD;
+
+            campaign.finalize(&mut ctx.accounts.platform_stats);
+            campaign.close(creator_info.clone())?;
+            closed += 1;
+        }
+
+        msg!("Expired empty campaigns: {}, keeper reward {}", closed, KEEPER_REWARD * closed as u64);
+        Ok(())
+    }
+
+
+    pub fn init_histogram(ctx: Context<InitHistogram>, period: i64) -> Result<()> {
+        if period <= 0 {
+            return err!(CrowdfundError::InvalidHistogramPeriod);
+        }
+
+        let histogram = &mut ctx.accounts.histogram;
+        histogram.campaign = ctx.accounts.campaign.key();
+        histogram.bump = ctx.bumps.histogram;
+        histogram.start = Clock::get()?.unix_timestamp;
+        histogram.period = period;
+        histogram.bins = [HistogramBin::default(); HISTOGRAM_BINS];
+
+        msg!("Contribution histogram opened: {} second buckets", period);
+        Ok(())
+    }
+
+
+    // Once a campaign has succeeded its funds belong to the creator, so backers
+    // may close their contribution record early to reclaim its rent.
+    pub fn close_contribution(ctx: Context<CloseContribution>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+
+        if campaign.cancelled || !campaign.is_payable() {
+            return err!(CrowdfundError::CampaignNotSuccessful);
+        }
+        campaign.assert_terminal(clock.unix_timestamp)?;
+
+
+        campaign.contributors -= 1;
+        campaign.track_counted(ctx.accounts.contribution.amount, 0);
+        msg!("Contribution closed: {} lamports stay with the campaign", ctx.accounts.contribution.amount);
+        Ok(())
+    }
+
+
+    pub fn withdraw_and_close(ctx: Context<WithdrawAndClose>) -> Result<()> {
+        let withdraw = &mut ctx.accounts.withdraw;
+
+
+        if withdraw.campaign.contributors != 0 {
+            return err!(CrowdfundError::ContributionsRemain);
+        }
+        withdraw.apply(&ctx.bumps.withdraw)?;
+        withdraw.campaign.assert_no_held_funds()?;
+
+
+        let dust = withdraw.vault.lamports();
+        if dust > 0 {
+            transfer_from_vault(
+                &withdraw.system_program,
+                &withdraw.vault,
+                ctx.bumps.withdraw.vault,
+                withdraw.campaign.key(),
+                withdraw.creator.to_account_info(),
+                dust,
+            )?;
+        }
+
+        if let Some(log) = &mut withdraw.state_log {
+            log.record(CampaignState::Closed, Clock::get()?.unix_timestamp, withdraw.creator.key());
+        }
+        withdraw.campaign.close(withdraw.creator.to_account_info())?;
+        msg!("Campaign settled and closed, swept {} lamports", dust);
+        Ok(())
+    }
+
+
+    pub fn contribution_status(ctx: Context<CampaignView>) -> Result<ContributionStatus> {
+        let clock = Clock::get()?;
+        let mut campaign = (*ctx.accounts.campaign).clone();
+
+        // Mirror Contribute::apply, which rolls a due deadline before checking it.
+        campaign.roll_if_due(clock.unix_timestamp);
+        Ok(campaign.contribution_status(clock.unix_timestamp))
+    }
+
+
+    // Permissionless: later tranches go to whoever settled the payout, so a
+    // backup or abandoned settlement doesn't depend on the original creator.
+    pub fn release_tranche(ctx: Context<ReleaseTranche>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+
+        if !campaign.claimed {
+            return err!(CrowdfundError::CampaignNotSuccessful);
+        }
+        let amount = campaign.tranche_unlocked(clock.unix_timestamp) - campaign.tranche_released;
+        if amount == 0 {
+            return err!(CrowdfundError::TrancheNotUnlocked);
+        }
+
+
+        transfer_from_vault(
+            &ctx.accounts.system_program,
+            &ctx.accounts.vault,
+            ctx.bumps.vault,
+            campaign.key(),
+            ctx.accounts.recipient.to_account_info(),
+            amount,
+        )?;
+
+        campaign.tranche_released += amount;
+        campaign.record_withdrawal(amount)?;
+        msg!("Tranche released: {} lamports ({} of {})", amount, campaign.tranche_released, campaign.tranche_total);
+        Ok(())
+    }
+
+
+    // Naive linear projection from the average raise rate since creation;
+    // ETA_UNKNOWN when nothing has been raised yet.
+    pub fn estimate_eta(ctx: Context<CampaignView>) -> Result<i64> {
+        let campaign = &ctx.accounts.campaign;
+        let now = Clock::get()?.unix_timestamp;
+
+        if campaign.is_goal_met() {
+            return Ok(now);
+        }
+        let elapsed = (now - campaign.created_at).max(1) as u128;
+        if campaign.raised == 0 {
+            return Ok(ETA_UNKNOWN);
+        }
+
+        let remaining = (campaign.goal - campaign.raised) as u128;
+        let seconds = (remaining * elapsed).div_ceil(campaign.raised as u128);
+        Ok(now.saturating_add(i64::try_from(seconds).unwrap_or(i64::MAX)))
+    }
+
+
+    pub fn deposit_conditional_match(ctx: Context<DepositConditionalMatch>, amount: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+        campaign.assert_active(clock.unix_timestamp)?;
+
+
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.sponsor.to_account_info(),
+                to: ctx.accounts.conditional_match.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, amount)?;
+
+        let conditional_match = &mut ctx.accounts.conditional_match;
+        conditional_match.campaign = campaign.key();
+        conditional_match.sponsor = ctx.accounts.sponsor.key();
+        conditional_match.amount += amount;
+        conditional_match.bump = ctx.bumps.conditional_match;
+        campaign.conditional_match_outstanding += amount;
+        msg!("Conditional match deposited: {} lamports", amount);
+        Ok(())
+    }
+
+
+    // Released matches go straight to the creator: they boost the payout but
+    // are not part of `raised`, so they must stay out of vault accounting.
+    pub fn release_conditional_match(ctx: Context<ReleaseConditionalMatch>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+
+        if campaign.cancelled || !campaign.is_payable() {
+            return err!(CrowdfundError::CampaignNotSuccessful);
+        }
+        campaign.assert_terminal(clock.unix_timestamp)?;
+
+
+        let amount = ctx.accounts.conditional_match.amount;
+        **ctx.accounts.conditional_match.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.creator.to_account_info().try_borrow_mut_lamports()? += amount;
+
+        campaign.conditional_match_outstanding -= amount;
+        msg!("Conditional match released: {} lamports", amount);
+        Ok(())
+    }
+
+
+    pub fn return_conditional_match(ctx: Context<ReturnConditionalMatch>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+
+        campaign.roll_if_due(clock.unix_timestamp);
+        if !campaign.cancelled {
+            if campaign.is_payable() {
+                return err!(CrowdfundError::GoalMetCannotRefund);
+            }
+            if !campaign.has_ended(clock.unix_timestamp) {
+                return err!(CrowdfundError::CampaignNotEnded);
+            }
+        }
+
+
+        let amount = ctx.accounts.conditional_match.amount;
+        campaign.conditional_match_outstanding -= amount;
+        msg!("Conditional match returned: {} lamports", amount);
+        Ok(())
+    }
+
+
+    pub fn block_donor(ctx: Context<UpdateCampaign>, donor: Pubkey) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        if donor == Pubkey::default() || campaign.is_blocked(&donor) {
+            return Ok(());
+        }
+        match campaign.blocked.iter_mut().find(|slot| **slot == Pubkey::default()) {
+            Some(slot) => *slot = donor,
+            None => return err!(CrowdfundError::BlocklistFull),
+        }
+
+        msg!("Donor blocked: {}", donor);
+        Ok(())
+    }
+
+
+    pub fn unblock_donor(ctx: Context<UpdateCampaign>, donor: Pubkey) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        if let Some(slot) = campaign.blocked.iter_mut().find(|slot| **slot == donor) {
+            *slot = Pubkey::default();
+        }
+
+        msg!("Donor unblocked: {}", donor);
+        Ok(())
+    }
+
+
+    pub fn get_refund_outstanding(ctx: Context<CampaignVaultView>) -> Result<RefundOutstanding> {
+        let clock = Clock::get()?;
+        let mut campaign = (*ctx.accounts.campaign).clone();
+
+        campaign.roll_if_due(clock.unix_timestamp);
+        if campaign.claimed || campaign.assert_refundable(clock.unix_timestamp).is_err() {
+            return Ok(RefundOutstanding { total_refundable: 0, open_contributions: 0 });
+        }
+
+        // Only backers' money is owed: the creator's match stays out of the
+        // total, and after an emergency withdrawal each refund pays the same
+        // pro-rata share take_refund_share gives it.
+        let owed = campaign.raised.saturating_sub(campaign.creator_matched);
+        let total_refundable = campaign
+            .take_refund_share(owed)
+            .min(campaign.refundable_balance(ctx.accounts.vault.lamports())?);
+
+        Ok(RefundOutstanding {
+            total_refundable,
+            open_contributions: campaign.contributors,
+        })
+    }
+
+
+    pub fn transfer_contribution(ctx: Context<TransferContribution>, target_campaign: Pubkey) -> Result<()> {
+        let source = &mut ctx.accounts.source;
+        let target = &mut ctx.accounts.target;
+        let amount = ctx.accounts.source_contribution.amount;
+        let donor = ctx.accounts.donor.key();
+        let clock = Clock::get()?;
+
+
+        if source.key() == target_campaign {
+            return err!(CrowdfundError::SameCampaign);
+        }
+        source.assert_unlocked(clock.unix_timestamp)?;
+        source.assert_active(clock.unix_timestamp)?;
+        // Part of the source vault was paid out early; moving the full amount
+        // would spend what the other backers are owed pro rata.
+        if source.withdrawn > 0 {
+            return err!(CrowdfundError::EmergencyWithdrawalTaken);
+        }
+        // The lamports stay locked on the platform, so release them from the
+        // source first or the target's capacity check would count them twice.
+        ctx.accounts.platform_stats.total_locked = ctx.accounts.platform_stats.total_locked.saturating_sub(amount);
+        prepare_contribution(
+            target,
+            &ctx.accounts.platform,
+            &ctx.accounts.platform_stats,
+            &donor,
+            amount,
+            clock.unix_timestamp,
+        )?;
+        assert_contribution_account(&ctx.accounts.target_contribution, &target.key(), &donor, ctx.program_id)?;
+        target.assert_state_log(&ctx.accounts.target_state_log)?;
+
+
+        transfer_from_vault(
+            &ctx.accounts.system_program,
+            &ctx.accounts.source_vault,
+            ctx.bumps.source_vault,
+            source.key(),
+            ctx.accounts.target_vault.to_account_info(),
+            amount,
+        )?;
+
+        source.raised -= amount;
+        source.contributors -= 1;
+        source.track_counted(amount, 0);
+        source.refresh_filter_key();
+
+        // Moved lamports were already counted toward loyalty when first
+        // contributed, so only the campaign side and the record are credited.
+        let contribution = &mut ctx.accounts.target_contribution;
+        credit_campaign(
+            target,
+            &ctx.accounts.target_vault,
+            &mut ctx.accounts.platform_stats,
+            ctx.accounts.target_state_log.as_mut(),
+            ctx.accounts.target_histogram.as_mut(),
+            donor,
+            contribution.donor == Pubkey::default(),
+            contribution.amount,
+            amount,
+            clock.unix_timestamp,
+        )?;
+
+        contribution.donor = donor;
+        contribution.campaign = target.key();
+        contribution.amount += amount;
+        contribution.bump = ctx.bumps.target_contribution;
+        if target.in_soft_close(clock.unix_timestamp) {
+            contribution.late = true;
+        }
+        msg!("Moved {} lamports to campaign {}", amount, target.name);
+        Ok(())
+    }
+
+
+    pub fn post_comment(ctx: Context<PostComment>, content: String) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let comment = &mut ctx.accounts.comment;
+
+
+        if content.is_empty() || content.len() > MAX_COMMENT_LEN {
+            return err!(CrowdfundError::InvalidCommentLength);
+        }
+
+        comment.campaign = campaign.key();
+        comment.author = ctx.accounts.author.key();
+        comment.index = campaign.comment_count;
+        comment.posted_at = Clock::get()?.unix_timestamp;
+        comment.hidden = false;
+        comment.content = content;
+        comment.bump = ctx.bumps.comment;
+        campaign.comment_count += 1;
+
+        msg!("Comment {} posted", comment.index);
+        Ok(())
+    }
+
+
+    pub fn hide_comment(ctx: Context<HideComment>, hidden: bool) -> Result<()> {
+        let comment = &mut ctx.accounts.comment;
+
+        comment.hidden = hidden;
+        msg!("Comment {} hidden: {}", comment.index, hidden);
+        Ok(())
+    }
+
+
+    pub fn create_and_seed(
+        ctx: Context<CreateAndSeed>,
+        name: String,
+        goal: u64,
+        deadline: i64,
+        raise_cap: u64,
+        metadata: CampaignMetadata,
+        amount: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = ctx.accounts.creator.key();
+        let clock = Clock::get()?;
+
+
+        validate_campaign_params(&name, &metadata, goal, deadline, raise_cap, clock.unix_timestamp)?;
+        check_plausibility(&ctx.accounts.platform, goal, deadline, clock.unix_timestamp)?;
+        let generation = open_campaign_slot(&ctx.accounts.platform, &mut ctx.accounts.platform_stats)?;
+
+        campaign.init(creator, ctx.bumps.campaign, name, goal, deadline, raise_cap);
+        campaign.generation = generation;
+        campaign.duration = deadline - clock.unix_timestamp;
+        campaign.created_at = clock.unix_timestamp;
+        campaign.clock_drift_tolerance = ctx.accounts.platform.clock_drift_tolerance;
+        campaign.uri = metadata.uri;
+        campaign.symbol = metadata.symbol;
+
+
+        prepare_contribution(
+            campaign,
+            &ctx.accounts.platform,
+            &ctx.accounts.platform_stats,
+            &creator,
+            amount,
+            clock.unix_timestamp,
+        )?;
+        assert_contribution_account(&ctx.accounts.contribution, &campaign.key(), &creator, ctx.program_id)?;
+        if ctx.accounts.creator.lamports() < amount {
+            return err!(CrowdfundError::InsufficientDonorFunds);
+        }
+
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.creator.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, amount)?;
+
+        record_contribution(
+            campaign,
+            &ctx.accounts.vault,
+            &mut ctx.accounts.contribution,
+            ctx.bumps.contribution,
+            &mut ctx.accounts.loyalty,
+            ctx.bumps.loyalty,
+            &mut ctx.accounts.platform_stats,
+            None,
+            None,
+            creator,
+            amount,
+            clock.unix_timestamp,
+        )?;
+
+        msg!("Campaign created and seeded! Name: {}, Goal: {} lamports, Seed: {}", campaign.name, goal, amount);
+        Ok(())
+    }
+
+
+    pub fn verify_vault_integrity(ctx: Context<VaultIntegrityView>) -> Result<VaultIntegrity> {
+        let campaign = &ctx.accounts.campaign;
+        let actual = ctx.accounts.vault.lamports();
+        let expected = campaign.raised.saturating_sub(campaign.withdrawn) + campaign.vault_reserve()?;
+        let discrepancy = actual as i64 - expected as i64;
+
+        Ok(VaultIntegrity {
+            consistent: discrepancy == 0,
+            expected,
+            actual,
+            discrepancy,
+        })
+    }
+
+
+    pub fn is_authorized(ctx: Context<AuthorizationView>, action: u8) -> Result<bool> {
+        let campaign = &ctx.accounts.campaign;
+        let signer = ctx.accounts.signer.key();
+        let clock = Clock::get()?;
+
+        // Mirrors the co-signature check in settle_payout and cancel_campaign.
+        let approved = !campaign.approver_required
+            || matches!(&ctx.accounts.approver, Some(approver) if approver.key() == campaign.approver);
+
+        let authorized = match action {
+            // The backup creator can take over the payout once the creator goes inactive.
+            ACTION_WITHDRAW => {
+                let backup_due = campaign.backup_creator != Pubkey::default()
+                    && signer == campaign.backup_creator
+                    && clock.unix_timestamp >= campaign.deadline + ctx.accounts.platform.backup_inactivity_timeout;
+                approved && (signer == campaign.creator || backup_due)
+            }
+            ACTION_CANCEL => approved && signer == campaign.creator,
+            ACTION_UPDATE => signer == campaign.creator,
+            ACTION_MODERATE => signer == ctx.accounts.platform.authority,
+            _ => return err!(CrowdfundError::UnknownAction),
+        };
+
+        Ok(authorized)
+    }
+
+
+    pub fn approve_contribution_delegate(
+        ctx: Context<ApproveContributionDelegate>,
+        session_key: Pubkey,
+        allowance: u64,
+    ) -> Result<()> {
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.donor.to_account_info(),
+                to: ctx.accounts.delegate.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, allowance)?;
+
+        let delegate = &mut ctx.accounts.delegate;
+        delegate.donor = ctx.accounts.donor.key();
+        delegate.session_key = session_key;
+        delegate.allowance = allowance;
+        delegate.spent = 0;
+        delegate.bump = ctx.bumps.delegate;
+
+        msg!("Session key {} may contribute up to {} lamports", session_key, allowance);
+        Ok(())
+    }
+
+
+    pub fn revoke_contribution_delegate(ctx: Context<RevokeContributionDelegate>) -> Result<()> {
+        let delegate = &ctx.accounts.delegate;
+
+        msg!("Session key {} revoked, {} lamports unspent", delegate.session_key, delegate.allowance - delegate.spent);
+        Ok(())
+    }
+
+
+    pub fn contribute_delegated(ctx: Context<ContributeDelegated>, amount: u64) -> Result<()> {
+        let delegate = &mut ctx.accounts.delegate;
+        let donor = delegate.donor;
+        let clock = Clock::get()?;
+
+
+        if amount > delegate.allowance - delegate.spent {
+            return err!(CrowdfundError::DelegateAllowanceExceeded);
+        }
+        prepare_contribution(
+            &mut ctx.accounts.campaign,
+            &ctx.accounts.platform,
+            &ctx.accounts.platform_stats,
+            &donor,
+            amount,
+            clock.unix_timestamp,
+        )?;
+        assert_contribution_account(&ctx.accounts.contribution, &ctx.accounts.campaign.key(), &donor, ctx.program_id)?;
+        ctx.accounts.campaign.assert_state_log(&ctx.accounts.state_log)?;
+
+
+        **delegate.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? += amount;
+        delegate.spent += amount;
+
+        // Attributed to the donor, so refunds and closes pay them rather than
+        // the session key.
+        record_contribution(
+            &mut ctx.accounts.campaign,
+            &ctx.accounts.vault,
+            &mut ctx.accounts.contribution,
+            ctx.bumps.contribution,
+            &mut ctx.accounts.loyalty,
+            ctx.bumps.loyalty,
+            &mut ctx.accounts.platform_stats,
+            ctx.accounts.state_log.as_mut(),
+            ctx.accounts.histogram.as_mut(),
+            donor,
+            amount,
+            clock.unix_timestamp,
+        )?;
+
+        msg!("Delegated contribution: {} lamports for {}. Total Raised: {}", amount, donor, ctx.accounts.campaign.raised);
+        Ok(())
+    }
+
+
+    // Weight follows the delegate when one is set, so a donor who delegated
+    // away no longer counts their own contribution.
+    pub fn get_voting_power<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CampaignView<'info>>,
+        voter: Pubkey,
+    ) -> Result<u64> {
+        let campaign_key = ctx.accounts.campaign.key();
+
+        let mut seen: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut power = 0u64;
+        for contribution_info in ctx.remaining_accounts.iter() {
+            if seen.contains(contribution_info.key) {
+                return err!(CrowdfundError::InvalidRemainingAccounts);
+            }
+            seen.push(contribution_info.key());
+
+            let contribution: Account<Contribution> = Account::try_from(contribution_info)?;
+            if contribution.campaign != campaign_key {
+                return err!(CrowdfundError::InvalidContributionAccount);
+            }
+            let holder = if contribution.delegate == Pubkey::default() {
+                contribution.donor
+            } else {
+                contribution.delegate
+            };
+            if holder == voter {
+                power += contribution.amount;
+            }
+        }
+
+        Ok(power)
+    }
+
+
+    pub fn approve_emergency_withdraw(ctx: Context<ModerateCampaign>, emergency_cap: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        if !campaign.verified {
+            return err!(CrowdfundError::CampaignNotVerified);
+        }
+
+        campaign.emergency_approved = true;
+        campaign.emergency_cap = emergency_cap;
+
+        msg!("Emergency withdrawal approved up to {} lamports", emergency_cap);
+        Ok(())
+    }
+
+
+    pub fn emergency_withdraw(ctx: Context<EmergencyWithdraw>, amount: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let clock = Clock::get()?;
+
+
+        // Only a live campaign qualifies; once it ends the normal withdraw
+        // path with its fee and vesting applies.
+        campaign.assert_active(clock.unix_timestamp)?;
+        if !campaign.verified {
+            return err!(CrowdfundError::CampaignNotVerified);
+        }
+        let report_threshold = ctx.accounts.platform.report_threshold;
+        if report_threshold != 0 && campaign.report_count >= report_threshold {
+            return err!(CrowdfundError::CampaignUnderReview);
+        }
+        if campaign.approver_required {
+            match &ctx.accounts.approver {
+                Some(approver) if approver.key() == campaign.approver => {}
+                _ => return err!(CrowdfundError::ApproverSignatureRequired),
+            }
+        }
+        if !campaign.emergency_approved {
+            return err!(CrowdfundError::EmergencyNotApproved);
+        }
+        if campaign.emergency_withdrawn + amount > campaign.emergency_cap
+            || amount > campaign.withdrawable(ctx.accounts.vault.lamports())?
+        {
+            return err!(CrowdfundError::EmergencyCapExceeded);
+        }
+
+
+        transfer_from_vault(
+            &ctx.accounts.system_program,
+            &ctx.accounts.vault,
+            ctx.bumps.vault,
+            campaign.key(),
+            ctx.accounts.creator.to_account_info(),
+            amount,
+        )?;
+        campaign.record_withdrawal(amount)?;
+        campaign.emergency_withdrawn += amount;
+
+        emit!(EmergencyWithdrawal {
+            campaign: campaign.key(),
+            amount,
+            total_withdrawn: campaign.emergency_withdrawn,
+        });
+        msg!("Emergency withdrawal: {} lamports ({} of {} used)", amount, campaign.emergency_withdrawn, campaign.emergency_cap);
+        Ok(())
+    }
+
+
+    pub fn batch_set_uri<'info>(ctx: Context<'_, '_, 'info, 'info, BatchSetUri<'info>>, uri: String) -> Result<()> {
+        let creator = ctx.accounts.creator.key();
+
+
+        if ctx.remaining_accounts.is_empty() {
+            return err!(CrowdfundError::InvalidRemainingAccounts);
+        }
+        if ctx.remaining_accounts.len() > MAX_BATCH_UPDATE {
+            return err!(CrowdfundError::BatchTooLarge);
+        }
+
+
+        for campaign_info in ctx.remaining_accounts.iter() {
+            let mut campaign: Account<Campaign> = Account::try_from(campaign_info)?;
+            if campaign.creator != creator {
+                return err!(CrowdfundError::NotCreator);
+            }
+            if campaign.name.len() + uri.len() + campaign.symbol.len() > MAX_STRINGS_TOTAL {
+                return err!(CrowdfundError::MetadataTooLarge);
+            }
+
+            campaign.uri = uri.clone();
+            campaign.exit(ctx.program_id)?;
+        }
+
+        msg!("URI updated on {} campaigns", ctx.remaining_accounts.len());
+        Ok(())
+    }
+
+
+    pub fn validate_contribution(ctx: Context<ValidateContribution>, amount: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        let mut campaign = (*ctx.accounts.campaign).clone();
+
+        prepare_contribution(
+            &mut campaign,
+            &ctx.accounts.platform,
+            &ctx.accounts.platform_stats,
+            &ctx.accounts.donor.key(),
+            amount,
+            clock.unix_timestamp,
+        )?;
+        // Unlike contribute, first-time rent has not been paid yet, so it is
+        // added for whichever of the donor's accounts would still be created.
+        let rent = Rent::get()?;
+        let mut required = amount;
+        if ctx.accounts.contribution.lamports() == 0 {
+            required += rent.minimum_balance(Contribution::LEN);
+        }
+        if ctx.accounts.loyalty.lamports() == 0 {
+            required += rent.minimum_balance(LoyaltyAccount::LEN);
+        }
+        if ctx.accounts.donor.lamports() < required {
+            return err!(CrowdfundError::InsufficientDonorFunds);
+        }
+        Ok(())
+    }
+}
+
+
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct Create<'info> {
+    #[account(
+        init, 
+        payer = creator, 
+        space = Campaign::LEN,
+        seeds = [b"campaign", creator.key().as_ref(), name.as_bytes()], 
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateDual<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = Campaign::LEN,
+        seeds = [b"campaign", creator.key().as_ref(), name.as_bytes()],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub approver: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateCampaignsBatch<'info> {
+    #[account(seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// `contribution` and `loyalty` are created by init_if_needed before `apply` runs
+// its deadline check, but a rejected contribution fails the whole transaction,
+// so the runtime rolls back those allocations and no rent is charged.
+#[derive(Accounts)]
+pub struct Contribute<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = Contribution::LEN,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = LoyaltyAccount::LEN,
+        seeds = [b"loyalty", donor.key().as_ref()],
+        bump
+    )]
+    pub loyalty: Account<'info, LoyaltyAccount>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+    #[account(
+        mut,
+        seeds = [b"histogram", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = histogram.bump
+    )]
+    pub histogram: Option<Account<'info, ContributionHistogram>>,
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Contribute<'info> {
+    pub fn apply(&mut self, bumps: &ContributeBumps, program_id: &Pubkey, amount: u64) -> Result<()> {
+        let donor = self.donor.key();
+        let clock = Clock::get()?;
+
+
+        prepare_contribution(
+            &mut self.campaign,
+            &self.platform,
+            &self.platform_stats,
+            &donor,
+            amount,
+            clock.unix_timestamp,
+        )?;
+        assert_contribution_account(&self.contribution, &self.campaign.key(), &donor, program_id)?;
+        self.campaign.assert_state_log(&self.state_log)?;
+        // First-time donors have already paid contribution/loyalty rent by this
+        // point, so the balance checked here is what remains for the transfer.
+        if self.donor.lamports() < amount {
+            return err!(CrowdfundError::InsufficientDonorFunds);
+        }
+
+
+        let cpi_context = CpiContext::new(
+            self.system_program.to_account_info(),
+            system_program::Transfer {
+                from: self.donor.to_account_info(),
+                to: self.vault.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, amount)?;
+
+        record_contribution(
+            &mut self.campaign,
+            &self.vault,
+            &mut self.contribution,
+            bumps.contribution,
+            &mut self.loyalty,
+            bumps.loyalty,
+            &mut self.platform_stats,
+            self.state_log.as_mut(),
+            self.histogram.as_mut(),
+            donor,
+            amount,
+            clock.unix_timestamp,
+        )?;
+        msg!("Contributed: {} lamports. Total Raised: {}", amount, self.campaign.raised);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, referrer: Pubkey)]
+pub struct ContributeReferred<'info> {
+    pub contribute: Contribute<'info>,
+    #[account(
+        init_if_needed,
+        payer = contribute.donor,
+        space = ReferralStats::LEN,
+        seeds = [b"referral", contribute.campaign.key().as_ref(), &contribute.campaign.generation.to_le_bytes(), referrer.as_ref()],
+        bump
+    )]
+    pub referral_stats: Account<'info, ReferralStats>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralReward<'info> {
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"referral", campaign.key().as_ref(), &campaign.generation.to_le_bytes(), referrer.key().as_ref()],
+        bump = referral_stats.bump,
+        has_one = referrer
+    )]
+    pub referral_stats: Account<'info, ReferralStats>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: SystemAccount<'info>,
+    #[account(mut)]
+    pub referrer: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(
+        mut,
+        has_one = creator @ CrowdfundError::NotCreator
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(
+        mut,
+        constraint = creator.key() != vault.key() @ CrowdfundError::InvalidRefundRecipient
+    )]
+    pub creator: Signer<'info>,
+    pub approver: Option<Signer<'info>>,
+    #[account(mut)]
+    pub first_contributor: Option<SystemAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Withdraw<'info> {
+    pub fn apply(&mut self, bumps: &WithdrawBumps) -> Result<()> {
+        let campaign = &mut self.campaign;
+        let creator = &mut self.creator;
+        let vault = &mut self.vault;
+        let clock = Clock::get()?;
+
+
+        if campaign.cancelled {
+            return err!(CrowdfundError::CampaignCancelled);
+        }
+        if !campaign.is_payable() {
+            return err!(CrowdfundError::GoalNotMet);
+        }
+        campaign.assert_terminal(clock.unix_timestamp)?;
+        if campaign.claimed {
+            return err!(CrowdfundError::AlreadyClaimed);
+        }
+        campaign.assert_state_log(&self.state_log)?;
+
+
+        let (payout, fee) = settle_payout(
+            campaign,
+            &self.platform,
+            &self.system_program,
+            vault,
+            bumps.vault,
+            creator.to_account_info(),
+            self.treasury.to_account_info(),
+            self.platform.success_fee_bps,
+            self.approver.as_ref(),
+            self.first_contributor.as_ref(),
+            clock.unix_timestamp,
+        )?;
+
+        if let Some(log) = &mut self.state_log {
+            log.record(CampaignState::Claimed, clock.unix_timestamp, creator.key());
+        }
+        let platform_stats = &mut self.platform_stats;
+        platform_stats.total_locked = platform_stats.total_locked.saturating_sub(campaign.raised);
+        campaign.finalize(platform_stats);
+        msg!("Withdrawn all funds: {} lamports (fee: {})", payout, fee);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct BackupWithdraw<'info> {
+    #[account(
+        mut,
+        constraint = campaign.backup_creator != Pubkey::default() @ CrowdfundError::NotBackupCreator,
+        has_one = backup_creator @ CrowdfundError::NotBackupCreator
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(mut)]
+    pub backup_creator: Signer<'info>,
+    pub approver: Option<Signer<'info>>,
+    #[account(mut)]
+    pub first_contributor: Option<SystemAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Refund<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(
+        mut,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump = contribution.bump,
+        has_one = donor
+    )]
+    pub contribution: Account<'info, Contribution>,
+    #[account(
+        mut,
+        seeds = [b"loyalty", donor.key().as_ref()],
+        bump = loyalty.bump
+    )]
+    pub loyalty: Account<'info, LoyaltyAccount>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+    #[account(
+        mut,
+        constraint = donor.key() != vault.key() @ CrowdfundError::InvalidRefundRecipient
+    )]
+    pub donor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DonateRefund<'info> {
+    #[account(
+        mut,
+        has_one = creator @ CrowdfundError::NotCreator
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = donor,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump = contribution.bump,
+        has_one = donor
+    )]
+    pub contribution: Account<'info, Contribution>,
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    #[account(mut)]
+    pub creator: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ShrinkCampaign<'info> {
+    #[account(
+        mut,
+        has_one = creator @ CrowdfundError::NotCreator
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePlatform<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = PlatformConfig::LEN,
+        seeds = [b"platform"],
+        bump
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = PlatformStats::LEN,
+        seeds = [b"platform_stats"],
+        bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePlatform<'info> {
+    #[account(
+        mut,
+        seeds = [b"platform"],
+        bump = platform.bump,
+        has_one = authority @ CrowdfundError::NotPlatformAuthority
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CampaignLive<'info> {
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+}
+
+#[derive(Accounts)]
+pub struct FundRebatePool<'info> {
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        init_if_needed,
+        payer = sponsor,
+        space = RebatePool::LEN,
+        seeds = [b"rebate_pool", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump
+    )]
+    pub rebate_pool: Account<'info, RebatePool>,
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRebate<'info> {
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"rebate_pool", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = rebate_pool.bump
+    )]
+    pub rebate_pool: Account<'info, RebatePool>,
+    #[account(
+        mut,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump = contribution.bump,
+        has_one = donor
+    )]
+    pub contribution: Account<'info, Contribution>,
+    #[account(mut)]
+    pub donor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyGoalReduction<'info> {
+    #[account(
+        mut,
+        has_one = creator @ CrowdfundError::NotCreator
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Salvage<'info> {
+    #[account(
+        mut,
+        has_one = creator @ CrowdfundError::NotCreator
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(creator: Pubkey, name: String)]
+pub struct CampaignExists<'info> {
+    /// CHECK: may be uninitialized; only its owner and discriminator are inspected.
+    #[account(
+        seeds = [b"campaign", creator.as_ref(), name.as_bytes()],
+        bump
+    )]
+    pub campaign: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(campaign: Pubkey, donor: Pubkey)]
+pub struct HasContributed<'info> {
+    /// CHECK: may be uninitialized; only its owner and discriminator are inspected.
+    #[account(
+        seeds = [b"contribution", campaign.as_ref(), donor.as_ref()],
+        bump
+    )]
+    pub contribution: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CampaignView<'info> {
+    pub campaign: Account<'info, Campaign>,
+}
+
+#[derive(Accounts)]
+pub struct CampaignVaultView<'info> {
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DonorRank<'info> {
+    pub campaign: Account<'info, Campaign>,
+    #[account(has_one = campaign @ CrowdfundError::InvalidContributionAccount)]
+    pub contribution: Account<'info, Contribution>,
+}
+
+#[derive(Accounts)]
+pub struct TopUpVaultRent<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CrankCleanup<'info> {
+    #[account(
+        mut,
+        close = creator,
+        has_one = creator @ CrowdfundError::NotCreator
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(mut)]
+    pub creator: SystemAccount<'info>,
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetAutoRefund<'info> {
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump = contribution.bump,
+        has_one = donor
+    )]
+    pub contribution: Account<'info, Contribution>,
+    pub donor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProcessAutoRefunds<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    pub keeper: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeriveAddresses {}
+
+#[derive(Accounts)]
+pub struct UpdateCampaign<'info> {
+    #[account(
+        mut,
+        has_one = creator @ CrowdfundError::NotCreator
+    )]
+    pub campaign: Account<'info, Campaign>,
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AppendDescriptionChunk<'info> {
+    #[account(
+        mut,
+        has_one = creator @ CrowdfundError::NotCreator
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        init,
+        payer = creator,
+        space = DescriptionChunk::LEN,
+        seeds = [b"description", campaign.key().as_ref(), &campaign.generation.to_le_bytes(), &[campaign.description_chunks]],
+        bump
+    )]
+    pub chunk: Account<'info, DescriptionChunk>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositRewardPool<'info> {
+    #[account(has_one = creator @ CrowdfundError::NotCreator)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = RewardPool::LEN,
+        seeds = [b"reward_pool", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimBonus<'info> {
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"reward_pool", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = reward_pool.bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+    #[account(
+        mut,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump = contribution.bump,
+        has_one = donor
+    )]
+    pub contribution: Account<'info, Contribution>,
+    #[account(mut)]
+    pub donor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ErrorCategoryView {}
+
+#[derive(Accounts)]
+pub struct RollDeadline<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+}
+
+#[derive(Accounts)]
+pub struct CloseCampaign<'info> {
+    #[account(
+        mut,
+        close = creator,
+        has_one = creator @ CrowdfundError::NotCreator
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenMicroPool<'info> {
+    #[account(has_one = creator @ CrowdfundError::NotCreator)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        init,
+        payer = creator,
+        space = MicroPool::LEN,
+        seeds = [b"micro_pool", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump
+    )]
+    pub micro_pool: Account<'info, MicroPool>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseMicroPool<'info> {
+    #[account(has_one = creator @ CrowdfundError::NotCreator)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"micro_pool", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = micro_pool.bump
+    )]
+    pub micro_pool: Account<'info, MicroPool>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MicroContribute<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"micro_pool", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = micro_pool.bump
+    )]
+    pub micro_pool: Account<'info, MicroPool>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+    #[account(
+        mut,
+        seeds = [b"histogram", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = histogram.bump
+    )]
+    pub histogram: Option<Account<'info, ContributionHistogram>>,
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MicroRefund<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"micro_pool", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = micro_pool.bump
+    )]
+    pub micro_pool: Account<'info, MicroPool>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReportCampaign<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        init,
+        payer = reporter,
+        space = Report::LEN,
+        seeds = [b"report", campaign.key().as_ref(), &campaign.generation.to_le_bytes(), reporter.key().as_ref()],
+        bump
+    )]
+    pub report: Account<'info, Report>,
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ModerateCampaign<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump,
+        has_one = authority @ CrowdfundError::NotPlatformAuthority
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RouteToCharity<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = donor,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump = contribution.bump,
+        has_one = donor
+    )]
+    pub contribution: Account<'info, Contribution>,
+    #[account(mut)]
+    pub donor: SystemAccount<'info>,
+    #[account(
+        mut,
+        address = campaign.charity_fallback @ CrowdfundError::NoCharityFallback
+    )]
+    pub charity: SystemAccount<'info>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    pub keeper: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeAbandoned<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump,
+        has_one = authority @ CrowdfundError::NotPlatformAuthority
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(
+        mut,
+        address = campaign.payout_target() @ CrowdfundError::InvalidPayoutAddress
+    )]
+    pub payout: SystemAccount<'info>,
+    pub authority: Signer<'info>,
+    pub approver: Option<Signer<'info>>,
+    #[account(mut)]
+    pub first_contributor: Option<SystemAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelCampaign<'info> {
+    #[account(
+        mut,
+        has_one = creator @ CrowdfundError::NotCreator
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    pub creator: Signer<'info>,
+    pub approver: Option<Signer<'info>>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+}
+
+#[derive(Accounts)]
+pub struct DepositFirstBackerIncentive<'info> {
+    #[account(
+        mut,
+        has_one = creator @ CrowdfundError::NotCreator
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundCreatorMatch<'info> {
+    #[account(
+        mut,
+        has_one = creator @ CrowdfundError::NotCreator
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitStateLog<'info> {
+    #[account(mut, has_one = creator @ CrowdfundError::NotCreator)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        init,
+        payer = creator,
+        space = StateLog::LEN,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump
+    )]
+    pub state_log: Account<'info, StateLog>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CoverShortfall<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump,
+        has_one = authority @ CrowdfundError::NotPlatformAuthority
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireEmptyBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitHistogram<'info> {
+    #[account(has_one = creator @ CrowdfundError::NotCreator)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        init,
+        payer = creator,
+        space = ContributionHistogram::LEN,
+        seeds = [b"histogram", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump
+    )]
+    pub histogram: Account<'info, ContributionHistogram>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseContribution<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        close = donor,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump = contribution.bump,
+        has_one = donor
+    )]
+    pub contribution: Account<'info, Contribution>,
+    #[account(mut)]
+    pub donor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawAndClose<'info> {
+    pub withdraw: Withdraw<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseTranche<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        address = campaign.tranche_recipient @ CrowdfundError::InvalidPayoutAddress
+    )]
+    pub recipient: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositConditionalMatch<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        init_if_needed,
+        payer = sponsor,
+        space = ConditionalMatch::LEN,
+        seeds = [b"conditional_match", campaign.key().as_ref(), &campaign.generation.to_le_bytes(), sponsor.key().as_ref()],
+        bump
+    )]
+    pub conditional_match: Account<'info, ConditionalMatch>,
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseConditionalMatch<'info> {
+    #[account(
+        mut,
+        has_one = creator @ CrowdfundError::NotCreator
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        close = sponsor,
+        seeds = [b"conditional_match", campaign.key().as_ref(), &campaign.generation.to_le_bytes(), sponsor.key().as_ref()],
+        bump = conditional_match.bump,
+        has_one = sponsor
+    )]
+    pub conditional_match: Account<'info, ConditionalMatch>,
+    #[account(mut)]
+    pub creator: SystemAccount<'info>,
+    #[account(mut)]
+    pub sponsor: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReturnConditionalMatch<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        close = sponsor,
+        seeds = [b"conditional_match", campaign.key().as_ref(), &campaign.generation.to_le_bytes(), sponsor.key().as_ref()],
+        bump = conditional_match.bump,
+        has_one = sponsor
+    )]
+    pub conditional_match: Account<'info, ConditionalMatch>,
+    #[account(mut)]
+    pub sponsor: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(target_campaign: Pubkey)]
+pub struct TransferContribution<'info> {
+    #[account(mut)]
+    pub source: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", source.key().as_ref()],
+        bump
+    )]
+    pub source_vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = donor,
+        seeds = [b"contribution", source.key().as_ref(), donor.key().as_ref()],
+        bump = source_contribution.bump,
+        has_one = donor
+    )]
+    pub source_contribution: Account<'info, Contribution>,
+    #[account(mut, address = target_campaign)]
+    pub target: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", target.key().as_ref()],
+        bump
+    )]
+    pub target_vault: SystemAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = Contribution::LEN,
+        seeds = [b"contribution", target.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    pub target_contribution: Account<'info, Contribution>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(
+        mut,
+        seeds = [b"state_log", target.key().as_ref(), &target.generation.to_le_bytes()],
+        bump = target_state_log.bump
+    )]
+    pub target_state_log: Option<Account<'info, StateLog>>,
+    #[account(
+        mut,
+        seeds = [b"histogram", target.key().as_ref(), &target.generation.to_le_bytes()],
+        bump = target_histogram.bump
+    )]
+    pub target_histogram: Option<Account<'info, ContributionHistogram>>,
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PostComment<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        seeds = [b"contribution", campaign.key().as_ref(), author.key().as_ref()],
+        bump = contribution.bump,
+        constraint = contribution.donor == author.key() @ CrowdfundError::NotBacker
+    )]
+    pub contribution: Account<'info, Contribution>,
+    #[account(
+        init,
+        payer = author,
+        space = Comment::LEN,
+        seeds = [b"comment", campaign.key().as_ref(), &campaign.generation.to_le_bytes(), &campaign.comment_count.to_le_bytes()],
+        bump
+    )]
+    pub comment: Account<'info, Comment>,
+    #[account(mut)]
+    pub author: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct HideComment<'info> {
+    #[account(has_one = creator @ CrowdfundError::NotCreator)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"comment", campaign.key().as_ref(), &campaign.generation.to_le_bytes(), &comment.index.to_le_bytes()],
+        bump = comment.bump
+    )]
+    pub comment: Account<'info, Comment>,
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateAndSeed<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = Campaign::LEN,
+        seeds = [b"campaign", creator.key().as_ref(), name.as_bytes()],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        init,
+        payer = creator,
+        space = Contribution::LEN,
+        seeds = [b"contribution", campaign.key().as_ref(), creator.key().as_ref()],
+        bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = LoyaltyAccount::LEN,
+        seeds = [b"loyalty", creator.key().as_ref()],
+        bump
+    )]
+    pub loyalty: Account<'info, LoyaltyAccount>,
+    #[account(seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VaultIntegrityView<'info> {
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AuthorizationView<'info> {
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    pub signer: Signer<'info>,
+    pub approver: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct ApproveContributionDelegate<'info> {
+    #[account(
+        init,
+        payer = donor,
+        space = ContributionDelegate::LEN,
+        seeds = [b"contribution_delegate", donor.key().as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub delegate: Account<'info, ContributionDelegate>,
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeContributionDelegate<'info> {
+    #[account(
+        mut,
+        close = donor,
+        has_one = donor
+    )]
+    pub delegate: Account<'info, ContributionDelegate>,
+    #[account(mut)]
+    pub donor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ContributeDelegated<'info> {
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"contribution_delegate", delegate.donor.as_ref(), session_key.key().as_ref()],
+        bump = delegate.bump,
+        has_one = session_key @ CrowdfundError::NotSessionKey
+    )]
+    pub delegate: Account<'info, ContributionDelegate>,
+    #[account(
+        init_if_needed,
+        payer = session_key,
+        space = Contribution::LEN,
+        seeds = [b"contribution", campaign.key().as_ref(), delegate.donor.as_ref()],
+        bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+    #[account(
+        init_if_needed,
+        payer = session_key,
+        space = LoyaltyAccount::LEN,
+        seeds = [b"loyalty", delegate.donor.as_ref()],
+        bump
+    )]
+    pub loyalty: Account<'info, LoyaltyAccount>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(
+        mut,
+        seeds = [b"state_log", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = state_log.bump
+    )]
+    pub state_log: Option<Account<'info, StateLog>>,
+    #[account(
+        mut,
+        seeds = [b"histogram", campaign.key().as_ref(), &campaign.generation.to_le_bytes()],
+        bump = histogram.bump
+    )]
+    pub histogram: Option<Account<'info, ContributionHistogram>>,
+    #[account(mut)]
+    pub session_key: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyWithdraw<'info> {
+    #[account(
+        mut,
+        has_one = creator @ CrowdfundError::NotCreator
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump,
+        has_one = authority @ CrowdfundError::NotPlatformAuthority
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = creator.key() != vault.key() @ CrowdfundError::InvalidRefundRecipient
+    )]
+    pub creator: Signer<'info>,
+    pub approver: Option<Signer<'info>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BatchSetUri<'info> {
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ValidateContribution<'info> {
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    /// CHECK: may be uninitialized; only its balance is inspected.
+    #[account(
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    pub contribution: UncheckedAccount<'info>,
+    /// CHECK: may be uninitialized; only its balance is inspected.
+    #[account(
+        seeds = [b"loyalty", donor.key().as_ref()],
+        bump
+    )]
+    pub loyalty: UncheckedAccount<'info>,
+    pub donor: SystemAccount<'info>,
+}
+
+
+
+#[account]
+pub struct Campaign {
+    pub creator: Pubkey,
+    pub goal: u64,
+    pub raised: u64,
+    pub deadline: i64,
+    pub claimed: bool,
+    pub bump: u8,
+    pub raise_cap: u64,
+    pub filter_key: u64,
+    pub name: String,
+    pub contributors: u32,
+    pub refunds_started: bool,
+    pub backup_creator: Pubkey,
+    pub contribution_init_mode: u8,
+    pub vault_rent_reserved: bool,
+    pub uri: String,
+    pub symbol: String,
+    pub hard_cap: bool,
+    pub goal_exactly_met: bool,
+    pub description_chunks: u8,
+    pub block_self_delegation: bool,
+    pub pending_goal: u64,
+    pub pending_goal_at: i64,
+    pub duration: i64,
+    pub max_rolls: u8,
+    pub roll_count: u8,
+    pub approver: Pubkey,
+    pub approver_required: bool,
+    pub report_count: u32,
+    pub category: u8,
+    pub verified: bool,
+    pub listed: bool,
+    pub contributions_frozen: bool,
+    pub charity_fallback: Pubkey,
+    pub finalized: bool,
+    pub payout_address: Pubkey,
+    pub created_at: i64,
+    pub cancelled: bool,
+    pub first_contributor: Pubkey,
+    pub first_backer_incentive: u64,
+    pub block_creator_first: bool,
+    pub image_hash: [u8; 32],
+    pub creator_match_pool: u64,
+    pub creator_matched: u64,
+    pub max_contribution_bps_of_goal: u16,
+    pub funding_mode: u8,
+    pub count_threshold: u64,
+    pub counted_contributors: u32,
+    pub base_contribution_cap: u64,
+    pub final_window_bps: u16,
+    pub final_cap_bps: u16,
+    pub tranche_count: u8,
+    pub tranche_interval: i64,
+    pub tranche_total: u64,
+    pub tranche_released: u64,
+    pub claimed_at: i64,
+    pub conditional_match_outstanding: u64,
+    pub blocked: [Pubkey; MAX_BLOCKED_DONORS],
+    pub clock_drift_tolerance: i64,
+    pub withdrawn: u64,
+    pub comment_count: u32,
+    pub soft_close_window: i64,
+    pub lock_until: i64,
+    pub emergency_approved: bool,
+    pub emergency_cap: u64,
+    pub emergency_withdrawn: u64,
+    pub tranche_recipient: Pubkey,
+    pub generation: u64,
+    pub state_log_enabled: bool,
+}
+
+impl Campaign {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 1 + 1 + 8 + 8 + 4 + 4 + 1 + 32 + 1 + 1 + 4 + 4 + MAX_STRINGS_TOTAL + 1 + 1 + 1 + 1 + 8 + 8 + 8 + 1 + 1 + 32 + 1 + 4 + 1 + 1 + 1 + 1 + 32 + 1 + 32 + 8 + 1 + 32 + 8 + 1 + 32 + 8 + 8 + 2 + 1 + 8 + 4 + 8 + 2 + 2 + 1 + 8 + 8 + 8 + 8 + 8 + 32 * MAX_BLOCKED_DONORS + 8 + 8 + 4 + 8 + 8 + 1 + 8 + 8 + 32 + 8 + 1;
+
+    pub fn init(&mut self, creator: Pubkey, bump: u8, name: String, goal: u64, deadline: i64, raise_cap: u64) {
+        self.creator = creator;
+        self.goal = goal;
+        self.raised = 0;
+        self.deadline = deadline;
+        self.claimed = false;
+        self.bump = bump;
+        self.raise_cap = raise_cap;
+        self.name = name;
+        self.contribution_init_mode = CONTRIBUTION_INIT_IF_NEEDED;
+        self.listed = true;
+        self.refresh_filter_key();
+    }
+
+    pub fn payout_target(&self) -> Pubkey {
+        if self.payout_address == Pubkey::default() {
+            self.creator
+        } else {
+            self.payout_address
+        }
+    }
+
+    // Once a state log is opened every transition has to be written to it, so
+    // a caller can't skip logging by leaving the optional account out.
+    pub fn assert_state_log<T>(&self, state_log: &Option<T>) -> Result<()> {
+        if self.state_log_enabled && state_log.is_none() {
+            return err!(CrowdfundError::StateLogRequired);
+        }
+        Ok(())
+    }
+
+    pub fn finalize(&mut self, stats: &mut PlatformStats) {
+        if !self.finalized {
+            self.finalized = true;
+            stats.active_campaigns = stats.active_campaigns.saturating_sub(1);
+        }
+    }
+
+    pub fn contribution_status(&self, now: i64) -> ContributionStatus {
+        if self.claimed {
+            ContributionStatus::Claimed
+        } else if self.cancelled {
+            ContributionStatus::Cancelled
+        } else if self.finalized {
+            ContributionStatus::Finalized
+        } else if self.has_ended(now) {
+            ContributionStatus::Ended
+        } else if self.contributions_frozen {
+            ContributionStatus::Frozen
+        } else if self.emergency_withdrawn > 0 {
+            ContributionStatus::EmergencyWithdrawn
+        } else if self.hard_cap && self.raised >= self.goal {
+            ContributionStatus::HardCapReached
+        } else if self.raise_cap != 0 && self.raised >= self.raise_cap {
+            ContributionStatus::RaiseCapReached
+        } else {
+            ContributionStatus::Open
+        }
+    }
+
+    pub fn assert_active(&self, now: i64) -> Result<()> {
+        if self.claimed {
+            return err!(CrowdfundError::AlreadyClaimed);
+        }
+        if self.cancelled {
+            return err!(CrowdfundError::CampaignCancelled);
+        }
+        if self.has_ended(now) {
+            return err!(CrowdfundError::CampaignEnded);
+        }
+        Ok(())
+    }
+
+    pub fn assert_terminal(&self, now: i64) -> Result<()> {
+        let hard_cap_filled = self.hard_cap && self.goal_exactly_met;
+        if !self.has_ended(now) && !self.claimed && !self.cancelled && !hard_cap_filled {
+            return err!(CrowdfundError::CampaignNotEnded);
+        }
+        Ok(())
+    }
+
+    // No refund or reduction may move backer funds before the lock while the
+    // campaign runs. A cancelled or claimed campaign is settled, so backers
+    // are not held to it any longer.
+    pub fn assert_unlocked(&self, now: i64) -> Result<()> {
+        if self.cancelled || self.claimed {
+            return Ok(());
+        }
+        if now < self.lock_until {
+            return err!(CrowdfundError::FundsLocked);
+        }
+        Ok(())
+    }
+
+    pub fn assert_refundable(&self, now: i64) -> Result<()> {
+        self.assert_unlocked(now)?;
+        self.assert_terminal(now)?;
+        if self.is_goal_met() && !self.cancelled {
+            return err!(CrowdfundError::GoalMetCannotRefund);
+        }
+        if self.funding_mode == FUNDING_MODE_KEEP_WHAT_YOU_RAISE && !self.cancelled {
+            return err!(CrowdfundError::RefundsDisabled);
+        }
+        Ok(())
+    }
+
+    // Every goal check goes through here so the comparison stays the same for
+    // withdraw, refunds and views.
+    pub fn is_goal_met(&self) -> bool {
+        self.raised >= self.goal
+    }
+
+    // Keep-what-you-raise campaigns pay out whatever was raised once terminal.
+    // The base cap shrinks to `final_cap_bps` of itself once less than
+    // `final_window_bps` of the campaign's window remains.
+    pub fn contribution_cap(&self, now: i64) -> u64 {
+        let window = (self.deadline - self.created_at).max(0) as u128;
+        let remaining = (self.deadline - now).max(0) as u128;
+        if remaining * 10_000 < window * self.final_window_bps as u128 {
+            fee_for(self.base_contribution_cap, self.final_cap_bps)
+        } else {
+            self.base_contribution_cap
+        }
+    }
+
+    pub fn assert_contribution_size(&self, amount: u64, now: i64) -> Result<()> {
+        let max_bps = self.max_contribution_bps_of_goal;
+        if max_bps != 0 && amount > fee_for(self.goal, max_bps) {
+            return err!(CrowdfundError::ContributionTooLargeForGoal);
+        }
+        if self.base_contribution_cap != 0 && amount > self.contribution_cap(now) {
+            return err!(CrowdfundError::ContributionCapExceeded);
+        }
+        Ok(())
+    }
+
+    // Campaign-side checks every contribution path runs, in the order the
+    // errors are reported; validate_contribution replays them as a view.
+    pub fn assert_accepts(&self, donor: &Pubkey, amount: u64, now: i64) -> Result<()> {
+        self.assert_active(now)?;
+        if self.contributions_frozen {
+            return err!(CrowdfundError::ContributionsFrozen);
+        }
+        // New backers would only get a pro-rata share back if the campaign fails.
+        if self.emergency_withdrawn > 0 {
+            return err!(CrowdfundError::EmergencyWithdrawalTaken);
+        }
+        if self.is_blocked(donor) {
+            return err!(CrowdfundError::DonorBlocked);
+        }
+        if self.raise_cap != 0 && self.raised + amount > self.raise_cap {
+            return err!(CrowdfundError::RaiseCapExceeded);
+        }
+        if self.hard_cap && self.raised + amount > self.goal {
+            return err!(CrowdfundError::HardCapExceeded);
+        }
+        self.assert_contribution_size(amount, now)
+    }
+
+    // The first tranche unlocks at withdrawal and one more every interval after.
+    pub fn tranche_unlocked(&self, now: i64) -> u64 {
+        if self.tranche_count <= 1 {
+            return self.tranche_total;
+        }
+        let elapsed = (now - self.claimed_at).max(0);
+        let unlocked = (1 + elapsed / self.tranche_interval).min(self.tranche_count as i64);
+        (self.tranche_total as u128 * unlocked as u128 / self.tranche_count as u128) as u64
+    }
+
+    // Closing the campaign would strand funds still owed to the creator or to
+    // conditional-match sponsors.
+    pub fn assert_no_held_funds(&self) -> Result<()> {
+        if self.tranche_released < self.tranche_total {
+            return err!(CrowdfundError::TranchesOutstanding);
+        }
+        if self.conditional_match_outstanding != 0 {
+            return err!(CrowdfundError::ConditionalMatchOutstanding);
+        }
+        Ok(())
+    }
+
+    pub fn is_blocked(&self, donor: &Pubkey) -> bool {
+        self.blocked.contains(donor)
+    }
+
+    // Single deadline boundary for contribute, settlement and refunds, pushed out
+    // by the platform's clock drift tolerance captured at creation.
+    pub fn has_ended(&self, now: i64) -> bool {
+        now >= self.deadline.saturating_add(self.clock_drift_tolerance)
+    }
+
+    // Contributions are still accepted in the soft-close window but flagged late;
+    // the hard deadline is unchanged.
+    pub fn in_soft_close(&self, now: i64) -> bool {
+        self.soft_close_window > 0 && now >= self.deadline - self.soft_close_window
+    }
+
+    pub fn is_payable(&self) -> bool {
+        self.is_goal_met() || self.funding_mode == FUNDING_MODE_KEEP_WHAT_YOU_RAISE
+    }
+
+    // `contributors` counts open contribution accounts and drives refund and
+    // close bookkeeping; `counted_contributors` is the public metric and only
+    // includes donors whose cumulative amount reaches `count_threshold`.
+    pub fn track_counted(&mut self, before: u64, after: u64) {
+        let threshold = self.count_threshold.max(1);
+        match (before >= threshold, after >= threshold) {
+            (false, true) => self.counted_contributors += 1,
+            (true, false) => self.counted_contributors -= 1,
+            _ => {}
+        }
+    }
+
+    pub fn status(&self) -> u8 {
+        if self.claimed {
+            STATUS_CLAIMED
+        } else if self.cancelled {
+            STATUS_CANCELLED
+        } else if self.refunds_started {
+            STATUS_REFUNDING
+        } else if self.is_goal_met() {
+            STATUS_GOAL_MET
+        } else {
+            STATUS_ACTIVE
+        }
+    }
+
+    pub fn refresh_filter_key(&mut self) {
+        let mut key = self.category as u64 | (self.status() as u64) << 8;
+        if self.verified {
+            key |= FILTER_VERIFIED_BIT;
+        }
+        if self.listed {
+            key |= FILTER_LISTED_BIT;
+        }
+        self.filter_key = key;
+    }
+
+    pub fn roll_if_due(&mut self, now: i64) -> bool {
+        let mut rolled = false;
+        while self.has_ended(now) && !self.is_goal_met() && self.roll_count < self.max_rolls && self.duration > 0 {
+            self.deadline += self.duration;
+            self.roll_count += 1;
+            rolled = true;
+        }
+        rolled
+    }
+
+    // Creator match lamports are not the backers' money, so refunds (and the
+    // last-refund sweep) leave them in the vault for the creator to recover.
+    pub fn refundable_balance(&self, vault_lamports: u64) -> Result<u64> {
+        Ok(vault_lamports.saturating_sub(self.vault_reserve()? + self.creator_matched))
+    }
+
+    // Payouts are capped at what was raised and not yet withdrawn; stray
+    // deposits stay in the vault for the close sweep.
+    pub fn withdrawable(&self, vault_lamports: u64) -> Result<u64> {
+        let in_vault = vault_lamports.saturating_sub(self.vault_reserve()?);
+        Ok(in_vault.min(self.raised.saturating_sub(self.withdrawn)))
+    }
+
+    // After an emergency withdrawal each refund pays its pro-rata share of what
+    // is left; the unfunded part leaves the books with the contribution so the
+    // ratio stays the same for every later refund.
+    pub fn take_refund_share(&mut self, amount: u64) -> u64 {
+        if self.withdrawn == 0 || self.raised == 0 {
+            return amount;
+        }
+        let share = (amount as u128 * (self.raised - self.withdrawn) as u128 / self.raised as u128) as u64;
+        self.withdrawn -= amount - share;
+        share
+    }
+
+    pub fn record_withdrawal(&mut self, amount: u64) -> Result<()> {
+        if self.withdrawn + amount > self.raised {
+            return err!(CrowdfundError::WithdrawExceedsRaised);
+        }
+        self.withdrawn += amount;
+        Ok(())
+    }
+
+    pub fn vault_reserve(&self) -> Result<u64> {
+        if self.vault_rent_reserved {
+            Ok(Rent::get()?.minimum_balance(0))
+        } else {
+            Ok(0)
+        }
+    }
+}
+
+#[account]
+pub struct Contribution {
+    pub donor: Pubkey,
+    pub campaign: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+    pub rebate_claimed: bool,
+    pub auto_refund: bool,
+    pub delegate: Pubkey,
+    pub bonus_claimed: u64,
+    pub late: bool,
+}
+
+impl Contribution {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 1 + 1 + 1 + 32 + 8 + 1;
+}
+
+#[account]
+pub struct PlatformConfig {
+    pub authority: Pubkey,
+    pub min_visible_raise: u64,
+    pub bump: u8,
+    pub success_fee_bps: u16,
+    pub failure_fee_bps: u16,
+    pub salvage_window: i64,
+    pub max_tvl: u64,
+    pub loyalty_clawback: bool,
+    pub backup_inactivity_timeout: i64,
+    pub referral_reward_bps: u16,
+    pub min_goal_per_day: u64,
+    pub max_goal_per_day: u64,
+    pub enforce_plausibility: bool,
+    pub goal_reduction_delay: i64,
+    pub report_threshold: u32,
+    pub charity_timeout: i64,
+    pub max_active_campaigns: u32,
+    pub abandon_window: i64,
+    pub abandon_penalty_bps: u16,
+    pub min_age_before_cancel: i64,
+    pub max_shortfall_cover: u64,
+    pub shortfall_band_bps: u16,
+    pub clock_drift_tolerance: i64,
+    pub goal_increment: u64,
+}
+
+impl PlatformConfig {
+    pub const LEN: usize = 8 + 32 + 8 + 1 + 2 + 2 + 8 + 8 + 1 + 8 + 2 + 8 + 8 + 1 + 8 + 4 + 8 + 4 + 8 + 2 + 8 + 8 + 2 + 8 + 8;
+}
+
+#[account]
+pub struct PlatformStats {
+    pub total_locked: u64,
+    pub bump: u8,
+    pub active_campaigns: u32,
+    pub campaigns_created: u64,
+}
+
+impl PlatformStats {
+    pub const LEN: usize = 8 + 8 + 1 + 4 + 8;
+}
+
+#[account]
+pub struct RebatePool {
+    pub campaign: Pubkey,
+    pub sponsor: Pubkey,
+    pub remaining: u64,
+    pub bump: u8,
+}
+
+impl RebatePool {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 1;
+}
+
+#[account]
+pub struct LoyaltyAccount {
+    pub donor: Pubkey,
+    pub points: u64,
+    pub lifetime_contributed: u64,
+    pub bump: u8,
+}
+
+impl LoyaltyAccount {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CampaignInfo {
+    pub creator: Pubkey,
     pub goal: u64,
     pub raised: u64,
     pub deadline: i64,
     pub claimed: bool,
+    pub contributors: u32,
+    pub contribution_init_mode: u8,
+    pub goal_met: bool,
+    pub counted_contributors: u32,
+}
+
+#[account]
+pub struct ReferralStats {
+    pub campaign: Pubkey,
+    pub referrer: Pubkey,
+    pub referred_amount: u64,
+    pub referred_count: u32,
+    pub reward_claimed: bool,
+    pub bump: u8,
+}
+
+impl ReferralStats {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 4 + 1 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CampaignMetadata {
+    pub uri: String,
+    pub symbol: String,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DerivedAddresses {
+    pub campaign: Pubkey,
+    pub campaign_bump: u8,
+    pub vault: Pubkey,
+    pub vault_bump: u8,
+    pub contribution: Pubkey,
+    pub contribution_bump: u8,
+}
+
+#[event]
+pub struct GoalReached {
+    pub campaign: Pubkey,
+    pub goal: u64,
+    pub raised: u64,
+}
+
+#[event]
+pub struct EmergencyWithdrawal {
+    pub campaign: Pubkey,
+    pub amount: u64,
+    pub total_withdrawn: u64,
+}
+
+#[account]
+pub struct DescriptionChunk {
+    pub campaign: Pubkey,
+    pub index: u8,
+    pub content: String,
+    pub bump: u8,
+}
+
+impl DescriptionChunk {
+    pub const LEN: usize = 8 + 32 + 1 + 4 + MAX_CHUNK_LEN + 1;
+}
+
+#[account]
+pub struct RewardPool {
+    pub campaign: Pubkey,
+    pub total: u64,
+    pub distributed: u64,
+    pub bump: u8,
+}
+
+impl RewardPool {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ErrorCategory {
+    Timing,
+    Funding,
+    Authorization,
+    Other,
+}
+
+pub const TIMING_ERRORS: &[CrowdfundError] = &[
+    CrowdfundError::DeadlineInPast,
+    CrowdfundError::CampaignNotEnded,
+    CrowdfundError::CampaignEnded,
+    CrowdfundError::SalvageWindowClosed,
+    CrowdfundError::BackupTimeoutNotElapsed,
+    CrowdfundError::GoalReductionTimelocked,
+    CrowdfundError::DeadlineNotRollable,
+    CrowdfundError::CharityTimeoutNotElapsed,
+    CrowdfundError::AbandonWindowNotElapsed,
+    CrowdfundError::CancelTooEarly,
+    CrowdfundError::TrancheNotUnlocked,
+    CrowdfundError::FundsLocked,
+    CrowdfundError::LockTooLong,
+];
+
+pub const FUNDING_ERRORS: &[CrowdfundError] = &[
+    CrowdfundError::GoalNotMet,
+    CrowdfundError::GoalMetCannotRefund,
+    CrowdfundError::RefundExceedsContribution,
+    CrowdfundError::RaiseCapExceeded,
+    CrowdfundError::RebatePoolExhausted,
+    CrowdfundError::GoalAlreadyMet,
+    CrowdfundError::NotNearMiss,
+    CrowdfundError::SalvageGoalTooHigh,
+    CrowdfundError::PlatformTvlCapReached,
+    CrowdfundError::CampaignNotSuccessful,
+    CrowdfundError::InsufficientTreasury,
+    CrowdfundError::HardCapExceeded,
+    CrowdfundError::CampaignHasFunds,
+    CrowdfundError::MicroContributionTooLarge,
+    CrowdfundError::InsufficientDonorFunds,
+    CrowdfundError::ShortfallNotCoverable,
+    CrowdfundError::ContributionTooLargeForGoal,
+    CrowdfundError::RefundsDisabled,
+    CrowdfundError::ContributionCapExceeded,
+    CrowdfundError::DelegateAllowanceExceeded,
+    CrowdfundError::EmergencyCapExceeded,
+    CrowdfundError::PercentageRoundsToZero,
+    CrowdfundError::EmergencyWithdrawalTaken,
+    CrowdfundError::MicroPoolNotEmpty,
+];
+
+pub const AUTHORIZATION_ERRORS: &[CrowdfundError] = &[
+    CrowdfundError::NotCreator,
+    CrowdfundError::NotPlatformAuthority,
+    CrowdfundError::NotBackupCreator,
+    CrowdfundError::ApproverSignatureRequired,
+    CrowdfundError::DonorBlocked,
+    CrowdfundError::NotBacker,
+    CrowdfundError::NotSessionKey,
+    CrowdfundError::EmergencyNotApproved,
+    CrowdfundError::CampaignNotVerified,
+];
+
+impl ErrorCategory {
+    pub fn of(code: u32) -> Self {
+        let matches = |group: &[CrowdfundError]| group.iter().any(|e| u32::from(*e) == code);
+
+        if matches(TIMING_ERRORS) {
+            ErrorCategory::Timing
+        } else if matches(FUNDING_ERRORS) {
+            ErrorCategory::Funding
+        } else if matches(AUTHORIZATION_ERRORS) {
+            ErrorCategory::Authorization
+        } else {
+            ErrorCategory::Other
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MicroEntry {
+    pub donor: Pubkey,
+    pub amount: u64,
+}
+
+#[account]
+pub struct MicroPool {
+    pub campaign: Pubkey,
+    pub bump: u8,
+    pub entries: Vec<MicroEntry>,
+}
+
+impl MicroPool {
+    pub const LEN: usize = 8 + 32 + 1 + 4 + MAX_MICRO_ENTRIES * (32 + 8);
+}
+
+#[account]
+pub struct Report {
+    pub campaign: Pubkey,
+    pub reporter: Pubkey,
+    pub bump: u8,
+}
+
+impl Report {
+    pub const LEN: usize = 8 + 32 + 32 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CampaignState {
+    Created,
+    Active,
+    Succeeded,
+    Failed,
+    Claimed,
+    Cancelled,
+    Closed,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct StateTransition {
+    pub state: CampaignState,
+    pub timestamp: i64,
+    pub actor: Pubkey,
+}
+
+/// Append-only audit trail of a campaign's lifecycle. Instructions that move
+/// the campaign between states (contribute, refund, withdraw, cancel, close)
+/// record into it when the log is passed; once full, the oldest entry is
+/// dropped so `entries` always holds the latest transitions in order.
+#[account]
+pub struct StateLog {
+    pub campaign: Pubkey,
+    pub bump: u8,
+    pub total: u32,
+    pub entries: Vec<StateTransition>,
+}
+
+impl StateLog {
+    pub const LEN: usize = 8 + 32 + 1 + 4 + 4 + MAX_STATE_LOG_ENTRIES * (1 + 8 + 32);
+
+    pub fn record(&mut self, state: CampaignState, timestamp: i64, actor: Pubkey) {
+        if self.entries.len() == MAX_STATE_LOG_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.entries.push(StateTransition { state, timestamp, actor });
+        self.total += 1;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct HistogramBin {
+    pub period_index: u64,
+    pub count: u32,
+    pub sum: u64,
+}
+
+/// Contributions bucketed by `period` seconds since `start`. Bucket `i` lives
+/// in bin `i % HISTOGRAM_BINS`; a bin whose `period_index` is stale is reset
+/// before reuse, so long campaigns keep only the most recent window.
+#[account]
+pub struct ContributionHistogram {
+    pub campaign: Pubkey,
+    pub bump: u8,
+    pub start: i64,
+    pub period: i64,
+    pub bins: [HistogramBin; HISTOGRAM_BINS],
+}
+
+impl ContributionHistogram {
+    pub const LEN: usize = 8 + 32 + 1 + 8 + 8 + HISTOGRAM_BINS * (8 + 4 + 8);
+
+    pub fn record(&mut self, now: i64, amount: u64) {
+        let period_index = (now - self.start).max(0) as u64 / self.period as u64;
+        let bin = &mut self.bins[(period_index % HISTOGRAM_BINS as u64) as usize];
+        if bin.period_index != period_index {
+            *bin = HistogramBin { period_index, count: 0, sum: 0 };
+        }
+        bin.count += 1;
+        bin.sum += amount;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ContributionStatus {
+    Open,
+    Claimed,
+    Cancelled,
+    Finalized,
+    Ended,
+    Frozen,
+    HardCapReached,
+    RaiseCapReached,
+    EmergencyWithdrawn,
+}
+
+#[account]
+pub struct ConditionalMatch {
+    pub campaign: Pubkey,
+    pub sponsor: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl ConditionalMatch {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RefundOutstanding {
+    pub total_refundable: u64,
+    pub open_contributions: u32,
+}
+
+#[account]
+pub struct Comment {
+    pub campaign: Pubkey,
+    pub author: Pubkey,
+    pub index: u32,
+    pub posted_at: i64,
+    pub hidden: bool,
+    pub content: String,
+    pub bump: u8,
+}
+
+impl Comment {
+    pub const LEN: usize = 8 + 32 + 32 + 4 + 8 + 1 + 4 + MAX_COMMENT_LEN + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VaultIntegrity {
+    pub consistent: bool,
+    pub expected: u64,
+    pub actual: u64,
+    pub discrepancy: i64,
+}
+
+// Escrows a donor's allowance so a session key can contribute on their behalf.
+#[account]
+pub struct ContributionDelegate {
+    pub donor: Pubkey,
+    pub session_key: Pubkey,
+    pub allowance: u64,
+    pub spent: u64,
     pub bump: u8,
 }
 
+impl ContributionDelegate {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 1;
+}
+
 
 
 #[error_code]
@@ -229,4 +5434,184 @@ pub enum CrowdfundError {
     NotCreator,
     #[msg("Goal met, cannot refund.")]
     GoalMetCannotRefund,
+    #[msg("Campaign has ended.")]
+    CampaignEnded,
+    #[msg("Refund exceeds contribution.")]
+    RefundExceedsContribution,
+    #[msg("Raise cap must be zero or at least the goal.")]
+    RaiseCapBelowGoal,
+    #[msg("Contribution would exceed the raise cap.")]
+    RaiseCapExceeded,
+    #[msg("Campaign account cannot shrink below its serialized size.")]
+    CampaignTooSmall,
+    #[msg("Invalid contribution account.")]
+    InvalidContributionAccount,
+    #[msg("Not the platform authority.")]
+    NotPlatformAuthority,
+    #[msg("Rebate already claimed.")]
+    RebateAlreadyClaimed,
+    #[msg("Rebate pool exhausted.")]
+    RebatePoolExhausted,
+    #[msg("Fee exceeds the maximum allowed.")]
+    FeeTooHigh,
+    #[msg("Campaign name cannot be empty.")]
+    NameEmpty,
+    #[msg("Campaign name exceeds 32 bytes.")]
+    NameTooLong,
+    #[msg("Salvage window closed.")]
+    SalvageWindowClosed,
+    #[msg("Goal already met.")]
+    GoalAlreadyMet,
+    #[msg("Campaign missed its goal by too much to salvage.")]
+    NotNearMiss,
+    #[msg("Salvaged goal must not exceed the raised amount.")]
+    SalvageGoalTooHigh,
+    #[msg("Platform TVL cap reached.")]
+    PlatformTvlCapReached,
+    #[msg("Not the backup creator.")]
+    NotBackupCreator,
+    #[msg("Backup creator inactivity timeout has not elapsed.")]
+    BackupTimeoutNotElapsed,
+    #[msg("Vault rent already reserved.")]
+    VaultRentAlreadyReserved,
+    #[msg("Contributions remain open.")]
+    ContributionsRemain,
+    #[msg("Campaign is not in a terminal state.")]
+    CampaignNotTerminal,
+    #[msg("Cannot refer yourself.")]
+    SelfReferral,
+    #[msg("Campaign has not been successfully claimed.")]
+    CampaignNotSuccessful,
+    #[msg("Reward already claimed.")]
+    RewardAlreadyClaimed,
+    #[msg("Treasury balance is insufficient.")]
+    InsufficientTreasury,
+    #[msg("Combined name, uri and symbol length exceeds the maximum.")]
+    MetadataTooLarge,
+    #[msg("Invalid remaining accounts.")]
+    InvalidRemainingAccounts,
+    #[msg("Contribution has not opted in to auto refund.")]
+    AutoRefundNotEnabled,
+    #[msg("Contribution would exceed the hard cap.")]
+    HardCapExceeded,
+    #[msg("Campaign already has contributions.")]
+    CampaignHasFunds,
+    #[msg("Batch arrays must be non-empty and of equal length.")]
+    BatchLengthMismatch,
+    #[msg("Too many campaigns in one batch.")]
+    BatchTooLarge,
+    #[msg("Campaign description has reached the chunk limit.")]
+    TooManyDescriptionChunks,
+    #[msg("Description chunk is empty or too long.")]
+    InvalidChunkLength,
+    #[msg("Delegating to yourself is not allowed for this campaign.")]
+    SelfDelegation,
+    #[msg("Goal and duration are an implausible combination.")]
+    ImplausibleCampaignParams,
+    #[msg("No unclaimed bonus remains for this contribution.")]
+    BonusAlreadyClaimed,
+    #[msg("No goal reduction has been announced.")]
+    NoPendingGoalReduction,
+    #[msg("Announced goal reduction is still timelocked.")]
+    GoalReductionTimelocked,
+    #[msg("Deadline is not due to roll or the roll cap is reached.")]
+    DeadlineNotRollable,
+    #[msg("The campaign approver must co-sign this action.")]
+    ApproverSignatureRequired,
+    #[msg("Amount exceeds the micro contribution limit.")]
+    MicroContributionTooLarge,
+    #[msg("Micro pool has no free donor slots.")]
+    MicroPoolFull,
+    #[msg("Donor has no micro contribution in this pool.")]
+    NoMicroContribution,
+    #[msg("Campaign has too many open reports and is under review.")]
+    CampaignUnderReview,
+    #[msg("Contributions are temporarily frozen.")]
+    ContributionsFrozen,
+    #[msg("Percentage must be between 1 and 10000 basis points.")]
+    InvalidPercentage,
+    #[msg("Campaign has no charity fallback configured.")]
+    NoCharityFallback,
+    #[msg("Charity fallback timeout has not elapsed.")]
+    CharityTimeoutNotElapsed,
+    #[msg("Platform has reached its active campaign limit.")]
+    TooManyActiveCampaigns,
+    #[msg("Campaign has not been inactive long enough to finalize.")]
+    AbandonWindowNotElapsed,
+    #[msg("Payout account does not match the campaign payout address.")]
+    InvalidPayoutAddress,
+    #[msg("Donor balance is too low for this contribution.")]
+    InsufficientDonorFunds,
+    #[msg("Campaign has been cancelled.")]
+    CampaignCancelled,
+    #[msg("Campaign is too new to be cancelled.")]
+    CancelTooEarly,
+    #[msg("First contributor account is missing or does not match.")]
+    InvalidFirstContributor,
+    #[msg("Shortfall is outside the platform's cover policy.")]
+    ShortfallNotCoverable,
+    #[msg("Contribution exceeds the allowed share of the goal.")]
+    ContributionTooLargeForGoal,
+    #[msg("Histogram period must be positive.")]
+    InvalidHistogramPeriod,
+    #[msg("Unknown funding mode.")]
+    InvalidFundingMode,
+    #[msg("Keep-what-you-raise campaigns do not refund.")]
+    RefundsDisabled,
+    #[msg("The vault cannot receive its own funds.")]
+    InvalidRefundRecipient,
+    #[msg("Contribution exceeds the current per-contribution cap.")]
+    ContributionCapExceeded,
+    #[msg("Tranche interval must be positive.")]
+    InvalidTrancheSchedule,
+    #[msg("No tranche has unlocked since the last release.")]
+    TrancheNotUnlocked,
+    #[msg("Vested tranches are still held in the vault.")]
+    TranchesOutstanding,
+    #[msg("Conditional matches are still held for this campaign.")]
+    ConditionalMatchOutstanding,
+    #[msg("This donor is blocked from the campaign.")]
+    DonorBlocked,
+    #[msg("Campaign blocklist is full.")]
+    BlocklistFull,
+    #[msg("Clock drift tolerance must be between 0 and 60 seconds.")]
+    InvalidClockDriftTolerance,
+    #[msg("Source and target campaign must differ.")]
+    SameCampaign,
+    #[msg("Withdrawal would exceed the amount raised.")]
+    WithdrawExceedsRaised,
+    #[msg("Comment is empty or too long.")]
+    InvalidCommentLength,
+    #[msg("Only backers of this campaign can do this.")]
+    NotBacker,
+    #[msg("Soft-close window must be between zero and the campaign duration.")]
+    InvalidSoftCloseWindow,
+    #[msg("Unknown action.")]
+    UnknownAction,
+    #[msg("Goal is not a multiple of the platform goal increment.")]
+    GoalNotAligned,
+    #[msg("Goal increment must be at least 1.")]
+    InvalidGoalIncrement,
+    #[msg("Contribution exceeds the session key's remaining allowance.")]
+    DelegateAllowanceExceeded,
+    #[msg("Signer is not the delegated session key.")]
+    NotSessionKey,
+    #[msg("Contributions are locked until the campaign's lock time.")]
+    FundsLocked,
+    #[msg("Emergency withdrawal has not been approved by the platform.")]
+    EmergencyNotApproved,
+    #[msg("Emergency withdrawal exceeds the approved amount.")]
+    EmergencyCapExceeded,
+    #[msg("Campaign is not verified.")]
+    CampaignNotVerified,
+    #[msg("Campaign has paid out an emergency withdrawal.")]
+    EmergencyWithdrawalTaken,
+    #[msg("Micro pool still holds refundable entries.")]
+    MicroPoolNotEmpty,
+    #[msg("Percentage of the remaining gap rounds down to zero lamports.")]
+    PercentageRoundsToZero,
+    #[msg("Campaign keeps a state log; pass it with this instruction.")]
+    StateLogRequired,
+    #[msg("Lock may not extend more than 30 days past the deadline.")]
+    LockTooLong,
 }
\ No newline at end of file
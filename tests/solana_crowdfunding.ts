@@ -1,7 +1,36 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { SolanaCrowdfunding } from "../target/types/solana_crowdfunding";
-import { assert } from "chai";
+import { assert, expect } from "chai";
+
+before(async () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  await program.methods
+    .initializePlatform(new anchor.BN(0))
+    .accounts({
+      authority: provider.wallet.publicKey,
+    })
+    .rpc();
+});
+
+// Accounts derived from a campaign are also seeded with its generation, so this
+// only works once the campaign exists.
+async function campaignChildPDA(
+  program: Program<SolanaCrowdfunding>,
+  prefix: string,
+  campaign: anchor.web3.PublicKey,
+  ...extra: Buffer[]
+): Promise<anchor.web3.PublicKey> {
+  const { generation } = await program.account.campaign.fetch(campaign);
+  return anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from(prefix), campaign.toBuffer(), generation.toArrayLike(Buffer, "le", 8), ...extra],
+    program.programId
+  )[0];
+}
 
 describe("solana_crowdfunding", () => {
 
@@ -10,8 +39,10 @@ describe("solana_crowdfunding", () => {
 
   const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
 
+  const campaignName = "solana_crowdfunding";
+
   const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer()],
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
     program.programId
   );
 
@@ -27,7 +58,7 @@ describe("solana_crowdfunding", () => {
     const deadline = new anchor.BN(Math.floor(Date.now() / 1000) + 3);
 
     await program.methods
-      .createCampaign(goal, deadline)
+      .createCampaign(campaignName, goal, deadline, new anchor.BN(0), null, { uri: "", symbol: "" })
       .accounts({
         creator: provider.wallet.publicKey,
       })
@@ -99,4 +130,6542 @@ describe("solana_crowdfunding", () => {
     assert.ok(vaultBalance === 0);
     console.log("   -> Vault kosong, dana berhasil ditarik!");
   });
-});
\ No newline at end of file
+});
+describe("donate_refund", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "donate_refund";
+
+  const creator = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), creator.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const [vaultPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("vault"), campaignPDA.toBuffer()],
+    program.programId
+  );
+
+  const [contributionPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("contribution"), campaignPDA.toBuffer(), provider.wallet.publicKey.toBuffer()],
+    program.programId
+  );
+
+  it("1. Create a campaign that will fail (Goal: 10 SOL)", async () => {
+    const sig = await provider.connection.requestAirdrop(creator.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(10000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({
+        creator: creator.publicKey,
+      })
+      .signers([creator])
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({
+        campaign: campaignPDA,
+        donor: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const contribution = await program.account.contribution.fetch(contributionPDA);
+    assert.ok(contribution.amount.eq(new anchor.BN(1000000000)));
+  });
+
+  it("2. Fail donate_refund before the deadline", async () => {
+    try {
+      await program.methods
+        .donateRefund()
+        .accounts({
+          campaign: campaignPDA,
+          creator: creator.publicKey,
+        })
+        .rpc();
+      assert.fail("donate_refund should fail before the deadline");
+    } catch (err) {
+      assert.include(err.toString(), "CampaignNotEnded");
+    }
+  });
+
+  it("3. Donor donates the refund to the creator", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    const creatorBefore = await provider.connection.getBalance(creator.publicKey);
+
+    await program.methods
+      .donateRefund()
+      .accounts({
+        campaign: campaignPDA,
+        creator: creator.publicKey,
+      })
+      .rpc();
+
+    const creatorAfter = await provider.connection.getBalance(creator.publicKey);
+    assert.equal(creatorAfter - creatorBefore, 1000000000);
+
+    const campaign = await program.account.campaign.fetch(campaignPDA);
+    assert.ok(campaign.raised.eqn(0));
+
+    const contribution = await provider.connection.getAccountInfo(contributionPDA);
+    assert.isNull(contribution);
+
+    const vaultBalance = await provider.connection.getBalance(vaultPDA);
+    assert.equal(vaultBalance, 0);
+  });
+
+  it("4. Fail refund after the contribution was donated", async () => {
+    try {
+      await program.methods
+        .refund(new anchor.BN(1000000000))
+        .accounts({
+          campaign: campaignPDA,
+        })
+        .rpc();
+      assert.fail("refund should fail once the contribution is closed");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("AccountNotInitialized");
+    }
+  });
+});
+
+describe("raise_cap", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "raise_cap";
+
+  const creator = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), creator.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. Fail to create a campaign with a raise cap below the goal", async () => {
+    const sig = await provider.connection.requestAirdrop(creator.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    try {
+      await program.methods
+        .createCampaign(campaignName, new anchor.BN(2000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(1000000000), null, { uri: "", symbol: "" })
+        .accounts({
+          creator: creator.publicKey,
+        })
+        .signers([creator])
+        .rpc();
+      assert.fail("raise cap below goal should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "RaiseCapBelowGoal");
+    }
+  });
+
+  it("2. Over-fund past the goal up to the raise cap (Goal: 1 SOL, Cap: 3 SOL)", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(3000000000), null, { uri: "", symbol: "" })
+      .accounts({
+        creator: creator.publicKey,
+      })
+      .signers([creator])
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(2000000000))
+      .accounts({
+        campaign: campaignPDA,
+        donor: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({
+        campaign: campaignPDA,
+        donor: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.ok(account.raised.eq(new anchor.BN(3000000000)));
+  });
+
+  it("3. Fail to contribute beyond the raise cap", async () => {
+    try {
+      await program.methods
+        .contribute(new anchor.BN(1))
+        .accounts({
+          campaign: campaignPDA,
+          donor: provider.wallet.publicKey,
+        })
+        .rpc();
+      assert.fail("contribution beyond the raise cap should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "RaiseCapExceeded");
+    }
+  });
+});
+
+describe("shrink_campaign", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "shrink_campaign";
+
+  const creator = anchor.web3.Keypair.generate();
+  const stranger = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), creator.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. Shrinking an already compact campaign keeps size and rent unchanged", async () => {
+    for (const kp of [creator, stranger]) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 1000000000);
+      await provider.connection.confirmTransaction(sig);
+    }
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({
+        creator: creator.publicKey,
+      })
+      .signers([creator])
+      .rpc();
+
+    const before = await provider.connection.getAccountInfo(campaignPDA);
+
+    await program.methods
+      .shrinkCampaign()
+      .accounts({
+        campaign: campaignPDA,
+        creator: creator.publicKey,
+      })
+      .signers([creator])
+      .rpc();
+
+    const after = await provider.connection.getAccountInfo(campaignPDA);
+    assert.equal(after.data.length, before.data.length);
+    assert.equal(after.lamports, before.lamports);
+
+    const rentExempt = await provider.connection.getMinimumBalanceForRentExemption(after.data.length);
+    assert.ok(after.lamports >= rentExempt);
+  });
+
+  it("2. Fail shrink from a non-creator", async () => {
+    try {
+      await program.methods
+        .shrinkCampaign()
+        .accounts({
+          campaign: campaignPDA,
+          creator: stranger.publicKey,
+        })
+        .signers([stranger])
+        .rpc();
+      assert.fail("shrink should be creator-only");
+    } catch (err) {
+      assert.include(err.toString(), "NotCreator");
+    }
+  });
+
+  it("3. Shrinking an oversized campaign refunds exactly the freed rent", async () => {
+    // Loaded at validator start from tests/fixtures/oversized_campaign.json:
+    // a 1200-byte campaign account, larger than Campaign::LEN, owned by this key.
+    const fixtureCreator = anchor.web3.Keypair.fromSecretKey(
+      Uint8Array.from([152, 59, 49, 93, 213, 117, 72, 131, 210, 11, 148, 61, 191, 201, 130, 70, 234, 126, 120, 186, 206, 30, 19, 0, 122, 189, 50, 120, 35, 248, 29, 179, 228, 127, 172, 101, 52, 129, 236, 42, 184, 189, 77, 223, 43, 164, 72, 16, 111, 4, 219, 226, 86, 64, 93, 24, 201, 39, 151, 252, 51, 127, 16, 211])
+    );
+    const oversizedPDA = new anchor.web3.PublicKey("35DLSgGdZ6eHS51ju7wZqSwX4q45A9rirBdrRvMcg2vg");
+    const sig = await provider.connection.requestAirdrop(fixtureCreator.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    const before = await provider.connection.getAccountInfo(oversizedPDA);
+    const creatorBefore = await provider.connection.getBalance(fixtureCreator.publicKey);
+
+    await program.methods
+      .shrinkCampaign()
+      .accounts({
+        campaign: oversizedPDA,
+        creator: fixtureCreator.publicKey,
+      })
+      .signers([fixtureCreator])
+      .rpc();
+
+    const after = await provider.connection.getAccountInfo(oversizedPDA);
+    const creatorAfter = await provider.connection.getBalance(fixtureCreator.publicKey);
+    const freed =
+      (await provider.connection.getMinimumBalanceForRentExemption(before.data.length)) -
+      (await provider.connection.getMinimumBalanceForRentExemption(after.data.length));
+
+    assert.isBelow(after.data.length, before.data.length);
+    assert.equal(after.lamports, await provider.connection.getMinimumBalanceForRentExemption(after.data.length));
+    assert.equal(before.lamports - after.lamports, freed);
+    assert.equal(creatorAfter - creatorBefore, freed);
+  });
+});
+
+describe("contribution account validation", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "contribution account validation";
+
+  // Loaded at validator start from tests/fixtures/foreign_contribution.json: a
+  // Contribution already sitting at this key's PDA for the campaign below, but
+  // recorded for a different donor.
+  const creator = anchor.web3.Keypair.fromSecretKey(
+    Uint8Array.from([153, 157, 159, 34, 84, 199, 213, 206, 158, 254, 109, 220, 66, 171, 87, 17, 204, 103, 230, 185, 202, 51, 227, 247, 219, 49, 124, 23, 16, 68, 151, 242, 180, 254, 119, 24, 219, 117, 209, 49, 160, 11, 181, 49, 97, 1, 47, 84, 78, 198, 98, 156, 101, 40, 254, 211, 59, 144, 80, 180, 216, 77, 135, 254])
+  );
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), creator.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. Fail contribute into a pre-existing contribution account", async () => {
+    const sig = await provider.connection.requestAirdrop(creator.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({
+        creator: creator.publicKey,
+      })
+      .signers([creator])
+      .rpc();
+
+    // The canonical PDA passes the seeds check, so the handler has to catch it.
+    const [foreignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("contribution"), campaignPDA.toBuffer(), creator.publicKey.toBuffer()],
+      program.programId
+    );
+    const foreign = await program.account.contribution.fetch(foreignPDA);
+    assert.isFalse(foreign.donor.equals(creator.publicKey));
+
+    try {
+      await program.methods
+        .contribute(new anchor.BN(100000000))
+        .accounts({
+          campaign: campaignPDA,
+          donor: creator.publicKey,
+        })
+        .signers([creator])
+        .rpc();
+      assert.fail("a contribution account recorded for another donor should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InvalidContributionAccount");
+    }
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.ok(account.raised.eqn(0));
+  });
+
+  it("2. Contribute with the canonical contribution PDA", async () => {
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({
+        campaign: campaignPDA,
+        donor: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const [contributionPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("contribution"), campaignPDA.toBuffer(), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const contribution = await program.account.contribution.fetch(contributionPDA);
+    assert.ok(contribution.donor.equals(provider.wallet.publicKey));
+    assert.ok(contribution.campaign.equals(campaignPDA));
+  });
+});
+
+describe("is_live", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "is_live";
+
+  const creator = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), creator.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const [platformPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("platform")],
+    program.programId
+  );
+
+  it("1. Set the platform min visible raise to 0.5 SOL", async () => {
+    await program.methods
+      .setMinVisibleRaise(new anchor.BN(500000000))
+      .accounts({
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const platform = await program.account.platformConfig.fetch(platformPDA);
+    assert.ok(platform.authority.equals(provider.wallet.publicKey));
+    assert.ok(platform.minVisibleRaise.eq(new anchor.BN(500000000)));
+  });
+
+  it("2. Empty campaign is not live", async () => {
+    const sig = await provider.connection.requestAirdrop(creator.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(2000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({
+        creator: creator.publicKey,
+      })
+      .signers([creator])
+      .rpc();
+
+    const live = await program.methods.isLive().accounts({ campaign: campaignPDA }).view();
+    assert.isFalse(live);
+  });
+
+  it("3. Campaign below the visible-raise floor is not live", async () => {
+    await program.methods
+      .contribute(new anchor.BN(400000000))
+      .accounts({
+        campaign: campaignPDA,
+        donor: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const live = await program.methods.isLive().accounts({ campaign: campaignPDA }).view();
+    assert.isFalse(live);
+  });
+
+  it("4. Campaign becomes live once the floor is crossed", async () => {
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({
+        campaign: campaignPDA,
+        donor: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const live = await program.methods.isLive().accounts({ campaign: campaignPDA }).view();
+    assert.isTrue(live);
+  });
+
+  it("5. A cancelled campaign is no longer live", async () => {
+    await program.methods
+      .setMinAgeBeforeCancel(new anchor.BN(0))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .cancelCampaign()
+      .accounts({ campaign: campaignPDA, creator: creator.publicKey, approver: null })
+      .signers([creator])
+      .rpc();
+    await program.methods
+      .setMinAgeBeforeCancel(new anchor.BN(60 * 60))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    const live = await program.methods.isLive().accounts({ campaign: campaignPDA }).view();
+    assert.isFalse(live);
+  });
+
+  it("6. Only the platform authority can change the floor", async () => {
+    try {
+      await program.methods
+        .setMinVisibleRaise(new anchor.BN(0))
+        .accounts({
+          authority: creator.publicKey,
+        })
+        .signers([creator])
+        .rpc();
+      assert.fail("non-authority should not update the platform");
+    } catch (err) {
+      assert.include(err.toString(), "NotPlatformAuthority");
+    }
+  });
+});
+
+describe("rebate pool", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "rebate pool";
+
+  const creator = anchor.web3.Keypair.generate();
+  const donors = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), creator.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  let rebatePoolPDA: anchor.web3.PublicKey;
+
+  it("1. Sponsor funds a rebate pool for two and a half rebates", async () => {
+    for (const kp of [creator, ...donors]) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 1000000000);
+      await provider.connection.confirmTransaction(sig);
+    }
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(5000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({
+        creator: creator.publicKey,
+      })
+      .signers([creator])
+      .rpc();
+
+    await program.methods
+      .fundRebatePool(new anchor.BN(12500))
+      .accounts({
+        campaign: campaignPDA,
+        sponsor: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    rebatePoolPDA = await campaignChildPDA(program, "rebate_pool", campaignPDA);
+    const pool = await program.account.rebatePool.fetch(rebatePoolPDA);
+    assert.ok(pool.remaining.eqn(12500));
+    assert.ok(pool.sponsor.equals(provider.wallet.publicKey));
+  });
+
+  it("2. Contributions draw down the pool until it is exhausted", async () => {
+    const expected = [5000, 5000, 2500];
+
+    for (let i = 0; i < donors.length; i++) {
+      await program.methods
+        .contribute(new anchor.BN(100000000))
+        .accounts({
+          campaign: campaignPDA,
+          donor: donors[i].publicKey,
+        })
+        .signers([donors[i]])
+        .rpc();
+
+      const before = await provider.connection.getBalance(donors[i].publicKey);
+
+      await program.methods
+        .claimRebate()
+        .accounts({
+          campaign: campaignPDA,
+          donor: donors[i].publicKey,
+        })
+        .signers([donors[i]])
+        .rpc();
+
+      const after = await provider.connection.getBalance(donors[i].publicKey);
+      assert.equal(after - before, expected[i]);
+    }
+
+    const pool = await program.account.rebatePool.fetch(rebatePoolPDA);
+    assert.ok(pool.remaining.eqn(0));
+  });
+
+  it("3. Fail to claim a rebate twice", async () => {
+    try {
+      await program.methods
+        .claimRebate()
+        .accounts({
+          campaign: campaignPDA,
+          donor: donors[0].publicKey,
+        })
+        .signers([donors[0]])
+        .rpc();
+      assert.fail("second rebate claim should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "RebateAlreadyClaimed");
+    }
+  });
+
+  it("4. Fail to claim from an exhausted pool", async () => {
+    const late = anchor.web3.Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(late.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({
+        campaign: campaignPDA,
+        donor: late.publicKey,
+      })
+      .signers([late])
+      .rpc();
+
+    try {
+      await program.methods
+        .claimRebate()
+        .accounts({
+          campaign: campaignPDA,
+          donor: late.publicKey,
+        })
+        .signers([late])
+        .rpc();
+      assert.fail("claim from an exhausted pool should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "RebatePoolExhausted");
+    }
+  });
+});
+
+describe("outcome fees", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "outcome fees";
+
+  const winner = anchor.web3.Keypair.generate();
+  const loser = anchor.web3.Keypair.generate();
+
+  const campaignOf = (creator: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), creator.toBuffer(), Buffer.from(campaignName)],
+      program.programId
+    )[0];
+
+  const [treasuryPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("treasury")],
+    program.programId
+  );
+
+  it("1. Fail to set a fee above the maximum", async () => {
+    try {
+      await program.methods
+        .setFeeRates(1001, 0)
+        .accounts({
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+      assert.fail("fee above the cap should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "FeeTooHigh");
+    }
+  });
+
+  it("2. Set success fee 5% and failure fee 1%", async () => {
+    await program.methods
+      .setFeeRates(500, 100)
+      .accounts({
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    for (const kp of [winner, loser]) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 1000000000);
+      await provider.connection.confirmTransaction(sig);
+
+      await program.methods
+        .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), null, { uri: "", symbol: "" })
+        .accounts({
+          creator: kp.publicKey,
+        })
+        .signers([kp])
+        .rpc();
+    }
+
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({
+        campaign: campaignOf(winner.publicKey),
+        donor: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(500000000))
+      .accounts({
+        campaign: campaignOf(loser.publicKey),
+        donor: provider.wallet.publicKey,
+      })
+      .rpc();
+  });
+
+  it("3. Withdrawal pays the success fee to the treasury", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    const treasuryBefore = await provider.connection.getBalance(treasuryPDA);
+    const creatorBefore = await provider.connection.getBalance(winner.publicKey);
+
+    await program.methods
+      .withdraw()
+      .accounts({
+        campaign: campaignOf(winner.publicKey),
+        creator: winner.publicKey,
+      })
+      .signers([winner])
+      .rpc();
+
+    const treasuryAfter = await provider.connection.getBalance(treasuryPDA);
+    const creatorAfter = await provider.connection.getBalance(winner.publicKey);
+    assert.equal(treasuryAfter - treasuryBefore, 50000000);
+    assert.equal(creatorAfter - creatorBefore, 950000000);
+  });
+
+  it("4. Refund pays the failure fee to the treasury", async () => {
+    const treasuryBefore = await provider.connection.getBalance(treasuryPDA);
+
+    await program.methods
+      .refund(new anchor.BN(500000000))
+      .accounts({
+        campaign: campaignOf(loser.publicKey),
+      })
+      .rpc();
+
+    const treasuryAfter = await provider.connection.getBalance(treasuryPDA);
+    assert.equal(treasuryAfter - treasuryBefore, 5000000);
+
+    await program.methods
+      .setFeeRates(0, 0)
+      .accounts({
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+  });
+});
+
+describe("campaign names", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignPDA = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const create = (name: string) =>
+    program.methods
+      .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accountsPartial({
+        campaign: campaignPDA(name),
+        creator: provider.wallet.publicKey,
+      })
+      .rpc();
+
+  it("1. Fail to create a campaign with an empty name", async () => {
+    try {
+      await create("");
+      assert.fail("empty name should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "NameEmpty");
+    }
+  });
+
+  it("2. Create a campaign named with exactly 32 ASCII bytes", async () => {
+    const name = "a".repeat(32);
+    await create(name);
+
+    const account = await program.account.campaign.fetch(campaignPDA(name));
+    assert.equal(account.name, name);
+  });
+
+  it("3. Create a campaign named with exactly 32 bytes of multibyte characters", async () => {
+    const name = "\u00e9".repeat(16);
+    assert.equal(Buffer.byteLength(name), 32);
+    await create(name);
+
+    const account = await program.account.campaign.fetch(campaignPDA(name));
+    assert.equal(account.name, name);
+  });
+
+  it("4. Create a campaign whose name mixes 1-, 2-, 3- and 4-byte characters", async () => {
+    const name = "a\u00e9\u20ac\u{1F680}".repeat(3);
+    assert.equal(Buffer.byteLength(name), 30);
+    await create(name);
+
+    const first = campaignPDA(name);
+    const second = campaignPDA(name);
+    assert.ok(first.equals(second));
+
+    const account = await program.account.campaign.fetch(first);
+    assert.equal(account.name, name);
+  });
+
+  it("5. Fail to create a campaign whose multibyte name is 33 bytes", async () => {
+    const name = "\u00e9".repeat(16) + "a";
+    assert.equal(Buffer.byteLength(name), 33);
+    try {
+      await program.methods
+        .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+        .accountsPartial({
+          campaign: campaignPDA("\u00e9".repeat(16)),
+          creator: provider.wallet.publicKey,
+        })
+        .rpc();
+      assert.fail("33-byte name should be rejected");
+    } catch (err) {
+      // The name is a PDA seed, so the runtime rejects it while deriving the
+      // campaign address, before the handler's NameTooLong check runs.
+      expect(err.logs.join("\n")).to.match(/seed is too long/i);
+    }
+  });
+});
+
+describe("salvage", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignPDA = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const launch = async (name: string, raised: number) => {
+    await program.methods
+      .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accountsPartial({
+        campaign: campaignPDA(name),
+        creator: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(raised))
+      .accounts({
+        campaign: campaignPDA(name),
+        donor: provider.wallet.publicKey,
+      })
+      .rpc();
+  };
+
+  it("1. Launch a near-miss, a refunded near-miss and a clear failure", async () => {
+    await launch("salvage near miss", 950000000);
+    await launch("salvage refunded", 950000000);
+    await launch("salvage clear fail", 500000000);
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+  });
+
+  it("2. Salvage a near-miss by reducing the goal to the raised amount", async () => {
+    await program.methods
+      .salvage(new anchor.BN(950000000))
+      .accounts({
+        campaign: campaignPDA("salvage near miss"),
+      })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA("salvage near miss"));
+    assert.ok(account.goal.eq(new anchor.BN(950000000)));
+
+    await program.methods
+      .withdraw()
+      .accounts({
+        campaign: campaignPDA("salvage near miss"),
+      })
+      .rpc();
+  });
+
+  it("3. Fail salvage once backers have started refunding", async () => {
+    await program.methods
+      .refund(new anchor.BN(100000000))
+      .accounts({
+        campaign: campaignPDA("salvage refunded"),
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .salvage(new anchor.BN(850000000))
+        .accounts({
+          campaign: campaignPDA("salvage refunded"),
+        })
+        .rpc();
+      assert.fail("salvage should be rejected once refunds began");
+    } catch (err) {
+      assert.include(err.toString(), "SalvageWindowClosed");
+    }
+  });
+
+  it("4. Fail salvage of a campaign that missed by too much", async () => {
+    try {
+      await program.methods
+        .salvage(new anchor.BN(500000000))
+        .accounts({
+          campaign: campaignPDA("salvage clear fail"),
+        })
+        .rpc();
+      assert.fail("salvage should be rejected for a clear failure");
+    } catch (err) {
+      assert.include(err.toString(), "NotNearMiss");
+    }
+  });
+
+  it("5. Fail salvage after the salvage window closes", async () => {
+    await launch("salvage late", 950000000);
+
+    await program.methods
+      .setSalvageWindow(new anchor.BN(1))
+      .accounts({
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    try {
+      await program.methods
+        .salvage(new anchor.BN(950000000))
+        .accounts({
+          campaign: campaignPDA("salvage late"),
+        })
+        .rpc();
+      assert.fail("salvage should be rejected after the window");
+    } catch (err) {
+      assert.include(err.toString(), "SalvageWindowClosed");
+    }
+
+    await program.methods
+      .setSalvageWindow(new anchor.BN(24 * 60 * 60))
+      .accounts({
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+  });
+});
+
+describe("platform tvl cap", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "platform tvl cap";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const [statsPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("platform_stats")],
+    program.programId
+  );
+
+  it("1. Contribute up to the TVL cap", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(5000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({
+        creator: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const stats = await program.account.platformStats.fetch(statsPDA);
+    await program.methods
+      .setMaxTvl(stats.totalLocked.add(new anchor.BN(1000000000)))
+      .accounts({
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({
+        campaign: campaignPDA,
+        donor: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const after = await program.account.platformStats.fetch(statsPDA);
+    assert.ok(after.totalLocked.eq(stats.totalLocked.add(new anchor.BN(1000000000))));
+  });
+
+  it("2. Fail to contribute beyond the TVL cap", async () => {
+    try {
+      await program.methods
+        .contribute(new anchor.BN(1))
+        .accounts({
+          campaign: campaignPDA,
+          donor: provider.wallet.publicKey,
+        })
+        .rpc();
+      assert.fail("contribution beyond the TVL cap should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "PlatformTvlCapReached");
+    }
+  });
+
+  it("3. Lifting the cap (0 = unlimited) accepts contributions again", async () => {
+    await program.methods
+      .setMaxTvl(new anchor.BN(0))
+      .accounts({
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(1))
+      .accounts({
+        campaign: campaignPDA,
+        donor: provider.wallet.publicKey,
+      })
+      .rpc();
+  });
+});
+
+describe("campaign_exists", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "campaign_exists";
+
+  it("1. Returns true for an existing campaign", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({
+        creator: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const exists = await program.methods
+      .campaignExists(provider.wallet.publicKey, campaignName)
+      .view();
+    assert.isTrue(exists);
+  });
+
+  it("2. Returns false for a campaign that was never created", async () => {
+    const exists = await program.methods
+      .campaignExists(provider.wallet.publicKey, "never created")
+      .view();
+    assert.isFalse(exists);
+  });
+});
+
+describe("loyalty points", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const donor = anchor.web3.Keypair.generate();
+
+  const campaignPDA = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const [loyaltyPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("loyalty"), donor.publicKey.toBuffer()],
+    program.programId
+  );
+
+  it("1. Points aggregate across contributions to multiple campaigns", async () => {
+    const sig = await provider.connection.requestAirdrop(donor.publicKey, 2000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    for (const name of ["loyalty a", "loyalty b"]) {
+      await program.methods
+        .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 8), new anchor.BN(0), null, { uri: "", symbol: "" })
+        .accountsPartial({
+          campaign: campaignPDA(name),
+          creator: provider.wallet.publicKey,
+        })
+        .rpc();
+    }
+
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA("loyalty a"), donor: donor.publicKey })
+      .signers([donor])
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(50000000))
+      .accounts({ campaign: campaignPDA("loyalty a"), donor: donor.publicKey })
+      .signers([donor])
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(250000000))
+      .accounts({ campaign: campaignPDA("loyalty b"), donor: donor.publicKey })
+      .signers([donor])
+      .rpc();
+
+    const loyalty = await program.account.loyaltyAccount.fetch(loyaltyPDA);
+    assert.ok(loyalty.points.eqn(400));
+    assert.ok(loyalty.lifetimeContributed.eq(new anchor.BN(400000000)));
+  });
+
+  it("2. Points persist after a refund by default", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 9000));
+
+    await program.methods
+      .refund(new anchor.BN(150000000))
+      .accounts({ campaign: campaignPDA("loyalty a"), donor: donor.publicKey })
+      .signers([donor])
+      .rpc();
+
+    const loyalty = await program.account.loyaltyAccount.fetch(loyaltyPDA);
+    assert.ok(loyalty.points.eqn(400));
+  });
+
+  it("3. Points are clawed back on refund when configured", async () => {
+    await program.methods
+      .setLoyaltyClawback(true)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .refund(new anchor.BN(250000000))
+      .accounts({ campaign: campaignPDA("loyalty b"), donor: donor.publicKey })
+      .signers([donor])
+      .rpc();
+
+    const loyalty = await program.account.loyaltyAccount.fetch(loyaltyPDA);
+    assert.ok(loyalty.points.eqn(150));
+
+    await program.methods
+      .setLoyaltyClawback(false)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("refund remainders", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "refund remainders";
+
+  const donors = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+  const amounts = [333333333, 333333334, 1];
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const [vaultPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("vault"), campaignPDA.toBuffer()],
+    program.programId
+  );
+
+  it("1. Contribute amounts that leave fee rounding remainders", async () => {
+    for (const kp of donors) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 1000000000);
+      await provider.connection.confirmTransaction(sig);
+    }
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(5000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 6), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({
+        creator: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    for (let i = 0; i < donors.length; i++) {
+      await program.methods
+        .contribute(new anchor.BN(amounts[i]))
+        .accounts({ campaign: campaignPDA, donor: donors[i].publicKey })
+        .signers([donors[i]])
+        .rpc();
+    }
+
+    const tx = new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: provider.wallet.publicKey,
+        toPubkey: vaultPDA,
+        lamports: 7,
+      })
+    );
+    await provider.sendAndConfirm(tx);
+
+    await program.methods
+      .setFeeRates(0, 33)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+
+  it("2. The last refund absorbs the remainder and the vault nets to zero", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 8000));
+
+    for (let i = 0; i < donors.length; i++) {
+      await program.methods
+        .refund(new anchor.BN(amounts[i]))
+        .accounts({ campaign: campaignPDA, donor: donors[i].publicKey })
+        .signers([donors[i]])
+        .rpc();
+    }
+
+    const vaultBalance = await provider.connection.getBalance(vaultPDA);
+    assert.equal(vaultBalance, 0);
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.ok(account.raised.eqn(0));
+
+    await program.methods
+      .setFeeRates(0, 0)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("backup creator", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const backup = anchor.web3.Keypair.generate();
+
+  const campaignPDA = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const launch = async (name: string) => {
+    await program.methods
+      .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), backup.publicKey, { uri: "", symbol: "" })
+      .accountsPartial({
+        campaign: campaignPDA(name),
+        creator: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({ campaign: campaignPDA(name), donor: provider.wallet.publicKey })
+      .rpc();
+  };
+
+  it("1. Backup is recorded at creation", async () => {
+    const sig = await provider.connection.requestAirdrop(backup.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await launch("backup primary");
+    await launch("backup takeover");
+
+    const account = await program.account.campaign.fetch(campaignPDA("backup primary"));
+    assert.ok(account.backupCreator.equals(backup.publicKey));
+
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+  });
+
+  it("2. Backup cannot withdraw before the inactivity timeout", async () => {
+    try {
+      await program.methods
+        .backupWithdraw()
+        .accounts({ campaign: campaignPDA("backup primary"), backupCreator: backup.publicKey })
+        .signers([backup])
+        .rpc();
+      assert.fail("backup withdraw should wait for the inactivity timeout");
+    } catch (err) {
+      assert.include(err.toString(), "BackupTimeoutNotElapsed");
+    }
+  });
+
+  it("3. Primary creator can always withdraw", async () => {
+    await program.methods
+      .withdraw()
+      .accounts({ campaign: campaignPDA("backup primary") })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA("backup primary"));
+    assert.isTrue(account.claimed);
+  });
+
+  it("4. Backup withdraws after the inactivity timeout", async () => {
+    await program.methods
+      .setBackupInactivityTimeout(new anchor.BN(1))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    const before = await provider.connection.getBalance(backup.publicKey);
+
+    await program.methods
+      .backupWithdraw()
+      .accounts({ campaign: campaignPDA("backup takeover"), backupCreator: backup.publicKey })
+      .signers([backup])
+      .rpc();
+
+    const after = await provider.connection.getBalance(backup.publicKey);
+    assert.equal(after - before, 1000000000);
+
+    await program.methods
+      .setBackupInactivityTimeout(new anchor.BN(180 * 24 * 60 * 60))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("contribution init mode", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "contribution init mode";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. Mode is recorded on the campaign and surfaced in the info view", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({
+        creator: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.contributionInitMode, 0);
+
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    const info = await program.methods.getCampaignInfo().accounts({ campaign: campaignPDA }).view();
+    assert.equal(info.contributionInitMode, 0);
+    assert.equal(info.contributors, 1);
+    assert.ok(info.raised.eq(new anchor.BN(100000000)));
+  });
+});
+
+describe("vault rent reserve", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "vault rent reserve";
+
+  const creator = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), creator.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const [vaultPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("vault"), campaignPDA.toBuffer()],
+    program.programId
+  );
+
+  it("1. Creator tops up the vault rent", async () => {
+    const sig = await provider.connection.requestAirdrop(creator.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 4), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: creator.publicKey })
+      .signers([creator])
+      .rpc();
+
+    await program.methods
+      .topUpVaultRent()
+      .accounts({ campaign: campaignPDA, payer: creator.publicKey })
+      .signers([creator])
+      .rpc();
+
+    const rentExempt = await provider.connection.getMinimumBalanceForRentExemption(0);
+    const vaultBalance = await provider.connection.getBalance(vaultPDA);
+    assert.equal(vaultBalance, rentExempt);
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.isTrue(account.vaultRentReserved);
+  });
+
+  it("2. Fail a second top up", async () => {
+    try {
+      await program.methods
+        .topUpVaultRent()
+        .accounts({ campaign: campaignPDA, payer: creator.publicKey })
+        .signers([creator])
+        .rpc();
+      assert.fail("second top up should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "VaultRentAlreadyReserved");
+    }
+  });
+
+  it("3. Withdrawal pays out only the surplus and leaves the vault rent-exempt", async () => {
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    const before = await provider.connection.getBalance(creator.publicKey);
+
+    await program.methods
+      .withdraw()
+      .accounts({ campaign: campaignPDA, creator: creator.publicKey })
+      .signers([creator])
+      .rpc();
+
+    const after = await provider.connection.getBalance(creator.publicKey);
+    assert.equal(after - before, 1000000000);
+
+    const rentExempt = await provider.connection.getMinimumBalanceForRentExemption(0);
+    const vault = await provider.connection.getAccountInfo(vaultPDA);
+    assert.isNotNull(vault);
+    assert.equal(vault.lamports, rentExempt);
+  });
+});
+
+describe("crank_cleanup", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "crank_cleanup";
+
+  const creator = anchor.web3.Keypair.generate();
+  const donors = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+  const keeper = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), creator.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const crank = () =>
+    program.methods
+      .crankCleanup()
+      .accounts({ campaign: campaignPDA, creator: creator.publicKey, keeper: keeper.publicKey })
+      .signers([keeper])
+      .rpc();
+
+  it("1. Fail cleanup while contributions remain", async () => {
+    for (const kp of [creator, keeper, ...donors]) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 1000000000);
+      await provider.connection.confirmTransaction(sig);
+    }
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(5000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 4), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: creator.publicKey })
+      .signers([creator])
+      .rpc();
+
+    for (const donor of donors) {
+      await program.methods
+        .contribute(new anchor.BN(100000000))
+        .accounts({ campaign: campaignPDA, donor: donor.publicKey })
+        .signers([donor])
+        .rpc();
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    await program.methods
+      .refund(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: donors[0].publicKey })
+      .signers([donors[0]])
+      .rpc();
+
+    try {
+      await crank();
+      assert.fail("cleanup should wait for every refund");
+    } catch (err) {
+      assert.include(err.toString(), "ContributionsRemain");
+    }
+  });
+
+  it("2. Crank cleans up a fully-refunded campaign and pays the keeper", async () => {
+    await program.methods
+      .refund(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: donors[1].publicKey })
+      .signers([donors[1]])
+      .rpc();
+
+    const keeperBefore = await provider.connection.getBalance(keeper.publicKey);
+    await crank();
+    const keeperAfter = await provider.connection.getBalance(keeper.publicKey);
+
+    assert.equal(keeperAfter - keeperBefore, 5000);
+
+    const campaign = await provider.connection.getAccountInfo(campaignPDA);
+    assert.isNull(campaign);
+  });
+});
+
+describe("referrals", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "referrals";
+
+  const referrer = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const [treasuryPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("treasury")],
+    program.programId
+  );
+
+  it("1. Fail a self-referral", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 5), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .contributeReferred(new anchor.BN(100000000), provider.wallet.publicKey)
+        .accountsPartial({
+          contribute: { campaign: campaignPDA, donor: provider.wallet.publicKey },
+        })
+        .rpc();
+      assert.fail("self-referral should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "SelfReferral");
+    }
+  });
+
+  it("2. Contributions are attributed to the referrer", async () => {
+    for (const amount of [600000000, 400000000]) {
+      await program.methods
+        .contributeReferred(new anchor.BN(amount), referrer.publicKey)
+        .accountsPartial({
+          contribute: { campaign: campaignPDA, donor: provider.wallet.publicKey },
+        })
+        .rpc();
+    }
+
+    const referralPDA = await campaignChildPDA(program, "referral", campaignPDA, referrer.publicKey.toBuffer());
+    const referral = await program.account.referralStats.fetch(referralPDA);
+    assert.ok(referral.referrer.equals(referrer.publicKey));
+    assert.ok(referral.referredAmount.eq(new anchor.BN(1000000000)));
+    assert.equal(referral.referredCount, 2);
+  });
+
+  it("3. Referrer claims a 1% reward from the treasury once the campaign succeeds", async () => {
+    await program.methods
+      .setReferralRewardBps(100)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    const tx = new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: provider.wallet.publicKey,
+        toPubkey: treasuryPDA,
+        lamports: 10000000,
+      })
+    );
+    await provider.sendAndConfirm(tx);
+
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    try {
+      await program.methods
+        .claimReferralReward()
+        .accounts({ campaign: campaignPDA, referrer: referrer.publicKey })
+        .rpc();
+      assert.fail("reward should wait for a successful withdrawal");
+    } catch (err) {
+      assert.include(err.toString(), "CampaignNotSuccessful");
+    }
+
+    await program.methods
+      .withdraw()
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    await program.methods
+      .claimReferralReward()
+      .accounts({ campaign: campaignPDA, referrer: referrer.publicKey })
+      .rpc();
+
+    const balance = await provider.connection.getBalance(referrer.publicKey);
+    assert.equal(balance, 10000000);
+
+    await program.methods
+      .setReferralRewardBps(0)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("campaign metadata", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignPDA = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const create = (name: string, uri: string, symbol: string) =>
+    program.methods
+      .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri, symbol })
+      .accountsPartial({
+        campaign: campaignPDA(name),
+        creator: provider.wallet.publicKey,
+      })
+      .rpc();
+
+  it("1. Create a campaign whose name, uri and symbol total exactly the cap", async () => {
+    const name = "metadata at cap";
+    const symbol = "FUND";
+    const uri = "https://example.com/" + "x".repeat(200 - name.length - symbol.length - 20);
+    assert.equal(name.length + uri.length + symbol.length, 200);
+
+    await create(name, uri, symbol);
+
+    const account = await program.account.campaign.fetch(campaignPDA(name));
+    assert.equal(account.uri, uri);
+    assert.equal(account.symbol, symbol);
+  });
+
+  it("2. Fail to create a campaign one byte beyond the cap", async () => {
+    const name = "metadata over cap";
+    const symbol = "FUND";
+    const uri = "https://example.com/" + "x".repeat(201 - name.length - symbol.length - 20);
+    assert.equal(name.length + uri.length + symbol.length, 201);
+
+    try {
+      await create(name, uri, symbol);
+      assert.fail("metadata beyond the cap should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "MetadataTooLarge");
+    }
+  });
+});
+
+describe("auto refunds", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "auto refunds";
+
+  const optedIn = anchor.web3.Keypair.generate();
+  const manual = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const contributionOf = (donor: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("contribution"), campaignPDA.toBuffer(), donor.toBuffer()],
+      program.programId
+    )[0];
+
+  const loyaltyOf = (donor: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("loyalty"), donor.toBuffer()],
+      program.programId
+    )[0];
+
+  const remainingFor = (donor: anchor.web3.PublicKey) => [
+    { pubkey: contributionOf(donor), isWritable: true, isSigner: false },
+    { pubkey: donor, isWritable: true, isSigner: false },
+    { pubkey: loyaltyOf(donor), isWritable: true, isSigner: false },
+  ];
+
+  it("1. One donor opts in to auto refund at contribution time", async () => {
+    for (const kp of [optedIn, manual]) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 1000000000);
+      await provider.connection.confirmTransaction(sig);
+    }
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(5000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 6), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(200000000))
+      .accounts({ campaign: campaignPDA, donor: optedIn.publicKey })
+      .postInstructions([
+        await program.methods
+          .setAutoRefund(true)
+          .accounts({ campaign: campaignPDA, donor: optedIn.publicKey })
+          .instruction(),
+      ])
+      .signers([optedIn])
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(300000000))
+      .accounts({ campaign: campaignPDA, donor: manual.publicKey })
+      .signers([manual])
+      .rpc();
+
+    const contribution = await program.account.contribution.fetch(contributionOf(optedIn.publicKey));
+    assert.isTrue(contribution.autoRefund);
+  });
+
+  it("2. Crank refunds the opted-in donor without their signature", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 8000));
+
+    const before = await provider.connection.getBalance(optedIn.publicKey);
+
+    await program.methods
+      .processAutoRefunds()
+      .accounts({ campaign: campaignPDA, keeper: provider.wallet.publicKey })
+      .remainingAccounts(remainingFor(optedIn.publicKey))
+      .rpc();
+
+    const after = await provider.connection.getBalance(optedIn.publicKey);
+    assert.isAtLeast(after - before, 200000000);
+    assert.isNull(await provider.connection.getAccountInfo(contributionOf(optedIn.publicKey)));
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.ok(account.raised.eq(new anchor.BN(300000000)));
+    assert.equal(account.contributors, 1);
+  });
+
+  it("3. Crank rejects donors who did not opt in", async () => {
+    try {
+      await program.methods
+        .processAutoRefunds()
+        .accounts({ campaign: campaignPDA, keeper: provider.wallet.publicKey })
+        .remainingAccounts(remainingFor(manual.publicKey))
+        .rpc();
+      assert.fail("non-opted donor should not be auto refunded");
+    } catch (err) {
+      assert.include(err.toString(), "AutoRefundNotEnabled");
+    }
+
+    await program.methods
+      .refund(new anchor.BN(300000000))
+      .accounts({ campaign: campaignPDA, donor: manual.publicKey })
+      .signers([manual])
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.ok(account.raised.eqn(0));
+  });
+});
+
+describe("derive_addresses", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  it("1. Returned addresses match independently derived PDAs", async () => {
+    const creator = provider.wallet.publicKey;
+    const donor = anchor.web3.Keypair.generate().publicKey;
+    const name = "derive \u00e9\u20ac";
+
+    const [campaign, campaignBump] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), creator.toBuffer(), Buffer.from(name)],
+      program.programId
+    );
+    const [vault, vaultBump] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), campaign.toBuffer()],
+      program.programId
+    );
+    const [contribution, contributionBump] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("contribution"), campaign.toBuffer(), donor.toBuffer()],
+      program.programId
+    );
+
+    const derived = await program.methods.deriveAddresses(creator, name, donor).view();
+
+    assert.ok(derived.campaign.equals(campaign));
+    assert.equal(derived.campaignBump, campaignBump);
+    assert.ok(derived.vault.equals(vault));
+    assert.equal(derived.vaultBump, vaultBump);
+    assert.ok(derived.contribution.equals(contribution));
+    assert.equal(derived.contributionBump, contributionBump);
+  });
+});
+
+describe("hard cap", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "hard cap";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. A contribution that exactly fills the goal is accepted and emits GoalReached", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .setHardCap(true)
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    let reached = null;
+    const listener = program.addEventListener("goalReached", (event) => {
+      reached = event;
+    });
+
+    await program.methods
+      .contribute(new anchor.BN(400000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(600000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+    await program.removeEventListener(listener);
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.isTrue(account.goalExactlyMet);
+    assert.ok(account.raised.eq(account.goal));
+    assert.isNotNull(reached);
+    assert.ok(reached.raised.eq(account.goal));
+  });
+
+  it("2. Even 1 lamport more is rejected", async () => {
+    try {
+      await program.methods
+        .contribute(new anchor.BN(1))
+        .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+        .rpc();
+      assert.fail("contribution past the hard cap should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "HardCapExceeded");
+    }
+  });
+
+  it("3. A filled hard-cap campaign can be withdrawn before the deadline", async () => {
+    await program.methods
+      .withdraw()
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.isTrue(account.claimed);
+  });
+
+  it("4. Hard cap cannot be toggled once funds exist", async () => {
+    try {
+      await program.methods
+        .setHardCap(false)
+        .accounts({ campaign: campaignPDA })
+        .rpc();
+      assert.fail("toggling the hard cap with funds should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "CampaignHasFunds");
+    }
+  });
+});
+
+describe("batch create", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  function batchAccounts(names: string[]) {
+    return names.flatMap((name) => {
+      const [campaign] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+        program.programId
+      );
+      const [vault] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), campaign.toBuffer()],
+        program.programId
+      );
+      return [
+        { pubkey: campaign, isWritable: true, isSigner: false },
+        { pubkey: vault, isWritable: false, isSigner: false },
+      ];
+    });
+  }
+
+  it("1. Creates three campaigns in one call", async () => {
+    const names = ["batch a", "batch b", "batch c"];
+    const deadline = new anchor.BN(Math.floor(Date.now() / 1000) + 60);
+    const accounts = batchAccounts(names);
+
+    await program.methods
+      .createCampaignsBatch(
+        names,
+        [new anchor.BN(1000), new anchor.BN(2000), new anchor.BN(3000)],
+        [deadline, deadline, deadline]
+      )
+      .accounts({ creator: provider.wallet.publicKey })
+      .remainingAccounts(accounts)
+      .rpc();
+
+    for (let i = 0; i < names.length; i++) {
+      const account = await program.account.campaign.fetch(accounts[i * 2].pubkey);
+      assert.equal(account.name, names[i]);
+      assert.equal(account.goal.toNumber(), (i + 1) * 1000);
+      assert.ok(account.creator.equals(provider.wallet.publicKey));
+    }
+  });
+
+  it("2. Rejects the whole batch when one entry is invalid", async () => {
+    const names = ["batch d", "batch e"];
+    const accounts = batchAccounts(names);
+
+    try {
+      await program.methods
+        .createCampaignsBatch(
+          names,
+          [new anchor.BN(1000), new anchor.BN(1000)],
+          [new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(1)]
+        )
+        .accounts({ creator: provider.wallet.publicKey })
+        .remainingAccounts(accounts)
+        .rpc();
+      assert.fail("batch with an invalid entry should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "DeadlineInPast");
+    }
+
+    const first = await provider.connection.getAccountInfo(accounts[0].pubkey);
+    assert.isNull(first);
+  });
+});
+
+describe("description chunks", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "description chunks";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  function chunkPDA(index: number) {
+    return campaignChildPDA(program, "description", campaignPDA, Buffer.from([index]));
+  }
+
+  const parts = ["Chapter one. ", "Chapter two. ", "Chapter three."];
+
+  it("1. Appends multiple chunks", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    for (const part of parts) {
+      await program.methods
+        .appendDescriptionChunk(part)
+        .accounts({ campaign: campaignPDA })
+        .rpc();
+    }
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.descriptionChunks, parts.length);
+  });
+
+  it("2. Reads chunks back in order", async () => {
+    let description = "";
+    for (let i = 0; i < parts.length; i++) {
+      const chunk = await program.account.descriptionChunk.fetch(await chunkPDA(i));
+      assert.equal(chunk.index, i);
+      description += chunk.content;
+    }
+    assert.equal(description, parts.join(""));
+  });
+
+  it("3. Rejects an empty chunk", async () => {
+    try {
+      await program.methods
+        .appendDescriptionChunk("")
+        .accounts({ campaign: campaignPDA })
+        .rpc();
+      assert.fail("empty chunk should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidChunkLength");
+    }
+  });
+});
+
+describe("contribute and delegate", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "contribute and delegate";
+  const delegate = anchor.web3.Keypair.generate().publicKey;
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+  const [contributionPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("contribution"), campaignPDA.toBuffer(), provider.wallet.publicKey.toBuffer()],
+    program.programId
+  );
+
+  it("1. Records the delegate and amount together", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .contributeAndDelegate(new anchor.BN(100000000), delegate)
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    const contribution = await program.account.contribution.fetch(contributionPDA);
+    assert.ok(contribution.delegate.equals(delegate));
+    assert.equal(contribution.amount.toNumber(), 100000000);
+  });
+
+  it("2. Rejects self delegation when the campaign blocks it", async () => {
+    await program.methods
+      .setBlockSelfDelegation(true)
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    try {
+      await program.methods
+        .contributeAndDelegate(new anchor.BN(100000000), provider.wallet.publicKey)
+        .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+        .rpc();
+      assert.fail("self delegation should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "SelfDelegation");
+    }
+  });
+});
+
+describe("plausibility check", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const deadline = () => new anchor.BN(Math.floor(Date.now() / 1000) + 60);
+
+  it("1. A reasonable goal and duration pass with enforcement on", async () => {
+    await program.methods
+      .setPlausibilityBounds(new anchor.BN(1000000), new anchor.BN("1000000000000000"), true)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .createCampaign("plausible", new anchor.BN(1000000000), deadline(), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+  });
+
+  it("2. An extreme mismatch is rejected when enforced", async () => {
+    try {
+      await program.methods
+        .createCampaign("implausible", new anchor.BN(1), deadline(), new anchor.BN(0), null, { uri: "", symbol: "" })
+        .accounts({ creator: provider.wallet.publicKey })
+        .rpc();
+      assert.fail("implausible campaign should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "ImplausibleCampaignParams");
+    }
+  });
+
+  it("3. The same mismatch only warns when enforcement is off", async () => {
+    await program.methods
+      .setPlausibilityBounds(new anchor.BN(1000000), new anchor.BN("1000000000000000"), false)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .createCampaign("implausible", new anchor.BN(1), deadline(), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .setPlausibilityBounds(new anchor.BN(0), new anchor.BN(0), false)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("donor rank", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "donor rank";
+  const second = anchor.web3.Keypair.generate();
+  const third = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  function contributionPDA(donor: anchor.web3.PublicKey) {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("contribution"), campaignPDA.toBuffer(), donor.toBuffer()],
+      program.programId
+    )[0];
+  }
+
+  const donors = [provider.wallet.publicKey, second.publicKey, third.publicKey];
+  const all = () => donors.map((d) => ({ pubkey: contributionPDA(d), isWritable: false, isSigner: false }));
+
+  it("1. Returns correct ranks for top contributors", async () => {
+    for (const kp of [second, third]) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 1000000000);
+      await provider.connection.confirmTransaction(sig);
+    }
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(5000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(200000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(500000000))
+      .accounts({ campaign: campaignPDA, donor: second.publicKey })
+      .signers([second])
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: third.publicKey })
+      .signers([third])
+      .rpc();
+
+    const ranks = [];
+    for (const donor of donors) {
+      ranks.push(
+        await program.methods
+          .getDonorRank()
+          .accounts({ campaign: campaignPDA, contribution: contributionPDA(donor) })
+          .remainingAccounts(all())
+          .view()
+      );
+    }
+    assert.deepEqual(ranks, [2, 1, 3]);
+  });
+
+  it("2. Rejects a contribution passed twice", async () => {
+    const duplicate = contributionPDA(donors[1]);
+    try {
+      await program.methods
+        .getDonorRank()
+        .accounts({ campaign: campaignPDA, contribution: contributionPDA(donors[2]) })
+        .remainingAccounts([...all(), { pubkey: duplicate, isWritable: false, isSigner: false }])
+        .view();
+      assert.fail("duplicate remaining accounts should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidRemainingAccounts");
+    }
+  });
+});
+
+describe("reward pool", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "reward pool";
+  const backer = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+  const [contributionPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("contribution"), campaignPDA.toBuffer(), provider.wallet.publicKey.toBuffer()],
+    program.programId
+  );
+
+  it("1. Creator deposits a reward pool after reaching the goal", async () => {
+    const sig = await provider.connection.requestAirdrop(backer.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 5), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(600000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(400000000))
+      .accounts({ campaign: campaignPDA, donor: backer.publicKey })
+      .signers([backer])
+      .rpc();
+
+    await program.methods
+      .depositRewardPool(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+  });
+
+  it("2. Two backers claim proportional bonuses", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    await program.methods
+      .claimBonus()
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    const before = await provider.connection.getBalance(backer.publicKey);
+    await program.methods
+      .claimBonus()
+      .accounts({ campaign: campaignPDA, donor: backer.publicKey })
+      .signers([backer])
+      .rpc();
+    const after = await provider.connection.getBalance(backer.publicKey);
+
+    const contribution = await program.account.contribution.fetch(contributionPDA);
+    assert.equal(contribution.bonusClaimed.toNumber(), 60000000);
+    assert.equal(after - before, 40000000);
+  });
+
+  it("3. A second claim is rejected", async () => {
+    try {
+      await program.methods
+        .claimBonus()
+        .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+        .rpc();
+      assert.fail("double claim should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "BonusAlreadyClaimed");
+    }
+  });
+});
+
+describe("goal reduction timelock", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "goal reduction timelock";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. An immediate goal-to-raised reduction is only announced", async () => {
+    await program.methods
+      .setGoalReductionDelay(new anchor.BN(4))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(950000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+
+    await program.methods
+      .salvage(new anchor.BN(950000000))
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.goal.toNumber(), 1000000000);
+    assert.equal(account.pendingGoal.toNumber(), 950000000);
+  });
+
+  it("2. Applying before the timelock elapses is rejected", async () => {
+    try {
+      await program.methods
+        .applyGoalReduction()
+        .accounts({ campaign: campaignPDA })
+        .rpc();
+      assert.fail("goal reduction should still be timelocked");
+    } catch (err) {
+      assert.include(err.toString(), "GoalReductionTimelocked");
+    }
+  });
+
+  it("3. The reduction takes effect after the timelock", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    await program.methods
+      .applyGoalReduction()
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.goal.toNumber(), 950000000);
+    assert.equal(account.pendingGoalAt.toNumber(), 0);
+
+    await program.methods
+      .setGoalReductionDelay(new anchor.BN(0))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("error categories", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const codeOf = (name: string) => program.idl.errors.find((e) => e.name.toLowerCase() === name.toLowerCase()).code;
+
+  const expected = {
+    campaignNotEnded: "timing",
+    campaignEnded: "timing",
+    deadlineInPast: "timing",
+    goalNotMet: "funding",
+    raiseCapExceeded: "funding",
+    goalMetCannotRefund: "funding",
+    notCreator: "authorization",
+    notPlatformAuthority: "authorization",
+    nameTooLong: "other",
+  };
+
+  it("1. Each error maps to the right group", async () => {
+    for (const [name, group] of Object.entries(expected)) {
+      const category = await program.methods.getErrorCategory(codeOf(name)).view();
+      assert.property(category, group, `${name} should be a ${group} error`);
+    }
+  });
+
+  it("2. An unknown code falls into the other group", async () => {
+    const category = await program.methods.getErrorCategory(1).view();
+    assert.property(category, "other");
+  });
+});
+
+describe("flexible deadline", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "flexible deadline";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. Rolls an unmet campaign forward twice", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 6), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .setMaxRolls(2)
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    const initial = await program.account.campaign.fetch(campaignPDA);
+
+    for (let roll = 1; roll <= 2; roll++) {
+      await new Promise((resolve) => setTimeout(resolve, 7000));
+      await program.methods
+        .rollDeadline()
+        .accounts({ campaign: campaignPDA })
+        .rpc();
+
+      const account = await program.account.campaign.fetch(campaignPDA);
+      assert.equal(account.rollCount, roll);
+      assert.equal(account.deadline.toNumber(), initial.deadline.toNumber() + roll * initial.duration.toNumber());
+    }
+  });
+
+  it("2. Fails after the roll cap and backers can refund", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 7000));
+
+    try {
+      await program.methods
+        .rollDeadline()
+        .accounts({ campaign: campaignPDA })
+        .rpc();
+      assert.fail("roll past the cap should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "DeadlineNotRollable");
+    }
+
+    await program.methods
+      .refund(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.raised.toNumber(), 0);
+  });
+});
+
+describe("close and re-create", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "close and re-create";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  let oldChunkPDA: anchor.web3.PublicKey;
+  let oldCommentPDA: anchor.web3.PublicKey;
+
+  // Gives the campaign a description chunk and a comment, both at index 0.
+  const addChunkAndComment = async (text: string) => {
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .appendDescriptionChunk(text)
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+    const comment = await campaignChildPDA(program, "comment", campaignPDA, Buffer.alloc(4));
+    await program.methods
+      .postComment(text)
+      .accounts({ campaign: campaignPDA, comment, author: provider.wallet.publicKey })
+      .rpc();
+    return { chunk: await campaignChildPDA(program, "description", campaignPDA, Buffer.from([0])), comment };
+  };
+
+  it("1. Closing is rejected while contributions remain", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    ({ chunk: oldChunkPDA, comment: oldCommentPDA } = await addChunkAndComment("first"));
+
+    try {
+      await program.methods
+        .closeCampaign()
+        .accounts({ campaign: campaignPDA })
+        .rpc();
+      assert.fail("close should be rejected while contributions remain");
+    } catch (err) {
+      assert.include(err.toString(), "ContributionsRemain");
+    }
+  });
+
+  it("2. Closes after refunds and re-creates with the same name from fresh state", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+
+    await program.methods
+      .refund(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    await program.methods
+      .closeCampaign()
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+    assert.isNull(await provider.connection.getAccountInfo(campaignPDA));
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(2000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.goal.toNumber(), 2000000000);
+    assert.equal(account.raised.toNumber(), 0);
+    assert.equal(account.contributors, 0);
+    assert.isFalse(account.refundsStarted);
+    assert.isFalse(account.claimed);
+  });
+
+  it("3. The re-created campaign gets its own description chunks and comments", async () => {
+    const { chunk, comment } = await addChunkAndComment("second");
+
+    assert.isFalse(chunk.equals(oldChunkPDA));
+    assert.isFalse(comment.equals(oldCommentPDA));
+    assert.equal((await program.account.descriptionChunk.fetch(chunk)).content, "second");
+    assert.equal((await program.account.comment.fetch(comment)).content, "second");
+    assert.equal((await program.account.descriptionChunk.fetch(oldChunkPDA)).content, "first");
+  });
+});
+
+describe("dual-signature creation", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "dual-signature creation";
+  const approver = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. A single-signed attempt fails", async () => {
+    try {
+      await program.methods
+        .createCampaignDual(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), { uri: "", symbol: "" }, true)
+        .accounts({ creator: provider.wallet.publicKey, approver: approver.publicKey })
+        .rpc();
+      assert.fail("creation without the approver signature should fail");
+    } catch (err) {
+      assert.match(err.toString(), /signature/i);
+    }
+  });
+
+  it("2. Dual-signed creation succeeds and stores the approver", async () => {
+    await program.methods
+      .createCampaignDual(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), { uri: "", symbol: "" }, true)
+      .accounts({ creator: provider.wallet.publicKey, approver: approver.publicKey })
+      .signers([approver])
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.ok(account.approver.equals(approver.publicKey));
+    assert.isTrue(account.approverRequired);
+  });
+
+  it("3. Withdraw requires the approver co-signature", async () => {
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+
+    try {
+      await program.methods
+        .withdraw()
+        .accounts({ campaign: campaignPDA, approver: null })
+        .rpc();
+      assert.fail("withdraw without the approver should fail");
+    } catch (err) {
+      assert.include(err.toString(), "ApproverSignatureRequired");
+    }
+
+    await program.methods
+      .withdraw()
+      .accounts({ campaign: campaignPDA, approver: approver.publicKey })
+      .signers([approver])
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.isTrue(account.claimed);
+  });
+});
+
+describe("micro pool", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "micro pool";
+  const donors = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+  let poolPDA: anchor.web3.PublicKey;
+
+  it("1. Pools several micro contributions without per-donor accounts", async () => {
+    for (const kp of donors) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 100000000);
+      await provider.connection.confirmTransaction(sig);
+    }
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 5), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .openMicroPool()
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+    poolPDA = await campaignChildPDA(program, "micro_pool", campaignPDA);
+
+    for (const [i, kp] of donors.entries()) {
+      const before = await provider.connection.getBalance(kp.publicKey);
+      await program.methods
+        .microContribute(new anchor.BN(1000 * (i + 1)))
+        .accounts({ campaign: campaignPDA, donor: kp.publicKey })
+        .signers([kp])
+        .rpc();
+      const after = await provider.connection.getBalance(kp.publicKey);
+      assert.equal(before - after, 1000 * (i + 1));
+    }
+
+    const pool = await program.account.microPool.fetch(poolPDA);
+    assert.equal(pool.entries.length, 3);
+    const campaign = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(campaign.raised.toNumber(), 6000);
+    assert.equal(campaign.contributors, 3);
+    // Credited through the same path as `contribute`, so the first backer is recorded.
+    assert.ok(campaign.firstContributor.equals(donors[0].publicKey));
+  });
+
+  it("2. Rejects amounts above the micro limit", async () => {
+    try {
+      await program.methods
+        .microContribute(new anchor.BN(1000001))
+        .accounts({ campaign: campaignPDA, donor: donors[0].publicKey })
+        .signers([donors[0]])
+        .rpc();
+      assert.fail("large micro contribution should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "MicroContributionTooLarge");
+    }
+  });
+
+  it("3. Refunds one donor from the pool", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    const before = await provider.connection.getBalance(donors[1].publicKey);
+    await program.methods
+      .microRefund()
+      .accounts({ campaign: campaignPDA, donor: donors[1].publicKey })
+      .signers([donors[1]])
+      .rpc();
+    const after = await provider.connection.getBalance(donors[1].publicKey);
+
+    assert.equal(after - before, 2000);
+    const pool = await program.account.microPool.fetch(poolPDA);
+    assert.equal(pool.entries.length, 2);
+    assert.isUndefined(pool.entries.find((e) => e.donor.equals(donors[1].publicKey)));
+  });
+
+  it("4. The creator recovers the pool rent once every entry is refunded", async () => {
+    try {
+      await program.methods.closeMicroPool().accounts({ campaign: campaignPDA }).rpc();
+      assert.fail("a pool with entries should not close");
+    } catch (err) {
+      assert.include(err.toString(), "MicroPoolNotEmpty");
+    }
+
+    for (const kp of [donors[0], donors[2]]) {
+      await program.methods
+        .microRefund()
+        .accounts({ campaign: campaignPDA, donor: kp.publicKey })
+        .signers([kp])
+        .rpc();
+    }
+
+    const rent = await provider.connection.getBalance(poolPDA);
+    const before = await provider.connection.getBalance(provider.wallet.publicKey);
+    await program.methods.closeMicroPool().accounts({ campaign: campaignPDA }).rpc();
+    const after = await provider.connection.getBalance(provider.wallet.publicKey);
+
+    assert.isNull(await provider.connection.getAccountInfo(poolPDA));
+    assert.equal(after - before, rent - 5000);
+  });
+});
+
+describe("report threshold", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "report threshold";
+  const reporters = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. Withdrawal is blocked once reports reach the threshold", async () => {
+    for (const kp of reporters) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 100000000);
+      await provider.connection.confirmTransaction(sig);
+    }
+
+    await program.methods
+      .setReportThreshold(2)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    for (const kp of reporters) {
+      await program.methods
+        .reportCampaign()
+        .accounts({ campaign: campaignPDA, reporter: kp.publicKey })
+        .signers([kp])
+        .rpc();
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+
+    try {
+      await program.methods
+        .withdraw()
+        .accounts({ campaign: campaignPDA, approver: null })
+        .rpc();
+      assert.fail("withdraw above the report threshold should be blocked");
+    } catch (err) {
+      assert.include(err.toString(), "CampaignUnderReview");
+    }
+  });
+
+  it("2. Withdrawal is allowed after the authority clears reports", async () => {
+    await program.methods
+      .clearReports()
+      .accounts({ campaign: campaignPDA, authority: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .withdraw()
+      .accounts({ campaign: campaignPDA, approver: null })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.isTrue(account.claimed);
+    assert.equal(account.reportCount, 0);
+
+    await program.methods
+      .setReportThreshold(0)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("filter key", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "filter key";
+  const FILTER_KEY_OFFSET = 8 + 32 + 8 + 8 + 8 + 1 + 1 + 8;
+  const LISTED = 1 << 17;
+  const VERIFIED = 1 << 16;
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const filterKey = async () => (await program.account.campaign.fetch(campaignPDA)).filterKey.toNumber();
+
+  it("1. A new campaign is listed, active and uncategorized", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    assert.equal(await filterKey(), LISTED);
+  });
+
+  it("2. Category and verification update the key", async () => {
+    await program.methods
+      .setCategory(7)
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+    assert.equal(await filterKey(), LISTED | 7);
+
+    await program.methods
+      .setVerified(true)
+      .accounts({ campaign: campaignPDA, authority: provider.wallet.publicKey })
+      .rpc();
+    assert.equal(await filterKey(), LISTED | VERIFIED | 7);
+  });
+
+  it("3. Reaching the goal updates the status bits", async () => {
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    assert.equal(await filterKey(), LISTED | VERIFIED | (1 << 8) | 7);
+  });
+
+  it("4. A single memcmp filter finds the campaign", async () => {
+    const key = Buffer.alloc(8);
+    key.writeBigUInt64LE(BigInt(LISTED | VERIFIED | (1 << 8) | 7));
+
+    const matches = await program.account.campaign.all([
+      { memcmp: { offset: FILTER_KEY_OFFSET, bytes: anchor.utils.bytes.bs58.encode(key) } },
+    ]);
+    assert.isTrue(matches.some((m) => m.publicKey.equals(campaignPDA)));
+  });
+});
+
+describe("freeze contributions", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "freeze contributions";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. Contributions fail while frozen and the deadline is unchanged", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    const before = await program.account.campaign.fetch(campaignPDA);
+
+    await program.methods
+      .freezeContributions()
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    try {
+      await program.methods
+        .contribute(new anchor.BN(100000000))
+        .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+        .rpc();
+      assert.fail("contribution while frozen should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "ContributionsFrozen");
+    }
+
+    const after = await program.account.campaign.fetch(campaignPDA);
+    assert.ok(after.deadline.eq(before.deadline));
+  });
+
+  it("2. Contributions resume after unfreeze", async () => {
+    await program.methods
+      .unfreezeContributions()
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.raised.toNumber(), 100000000);
+  });
+});
+
+describe("last refund sweep", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "last refund sweep";
+  const other = anchor.web3.Keypair.generate();
+  const stray = 50000000;
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+  const [vaultPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("vault"), campaignPDA.toBuffer()],
+    program.programId
+  );
+
+  it("1. Two backers contribute and stray lamports are injected", async () => {
+    const sig = await provider.connection.requestAirdrop(other.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 4), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(200000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(300000000))
+      .accounts({ campaign: campaignPDA, donor: other.publicKey })
+      .signers([other])
+      .rpc();
+
+    const tx = new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({ fromPubkey: provider.wallet.publicKey, toPubkey: vaultPDA, lamports: stray })
+    );
+    await provider.sendAndConfirm(tx);
+  });
+
+  it("2. A non-last refund pays exactly its amount, even a partial one by the last backer", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    let before = await provider.connection.getBalance(other.publicKey);
+    await program.methods
+      .refund(new anchor.BN(300000000))
+      .accounts({ campaign: campaignPDA, donor: other.publicKey })
+      .signers([other])
+      .rpc();
+    let after = await provider.connection.getBalance(other.publicKey);
+    assert.isAtLeast(after - before, 300000000);
+    assert.isBelow(after - before, 300000000 + stray);
+
+    const vaultBefore = await provider.connection.getBalance(vaultPDA);
+    await program.methods
+      .refund(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+    assert.equal(vaultBefore - (await provider.connection.getBalance(vaultPDA)), 100000000);
+  });
+
+  it("3. Only the true last refund sweeps the remainder", async () => {
+    await program.methods
+      .refund(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    assert.equal(await provider.connection.getBalance(vaultPDA), 0);
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.contributors, 0);
+  });
+});
+
+describe("state assertions", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignPDA = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const launch = async (name: string, seconds: number, raised: number) => {
+    await program.methods
+      .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + seconds), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(raised))
+      .accounts({ campaign: campaignPDA(name), donor: provider.wallet.publicKey })
+      .rpc();
+  };
+
+  const expectError = async (call: Promise<string>, error: string) => {
+    try {
+      await call;
+      assert.fail(`expected ${error}`);
+    } catch (err) {
+      assert.equal(err.error?.errorCode?.code, error, err.toString());
+    }
+  };
+
+  it("1. Active campaign: contribute passes, withdraw and refund are not terminal", async () => {
+    await launch("assert active", 60, 1000000000);
+
+    await expectError(
+      program.methods.withdraw().accounts({ campaign: campaignPDA("assert active"), approver: null }).rpc(),
+      "CampaignNotEnded"
+    );
+    await expectError(
+      program.methods.refund(new anchor.BN(1)).accounts({ campaign: campaignPDA("assert active") }).rpc(),
+      "CampaignNotEnded"
+    );
+  });
+
+  it("2. Ended campaigns: contribute is rejected, failed ones refund and successful ones withdraw", async () => {
+    await launch("assert ended ok", 3, 1000000000);
+    await launch("assert ended failed", 3, 100000000);
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+
+    for (const name of ["assert ended ok", "assert ended failed"]) {
+      await expectError(
+        program.methods.contribute(new anchor.BN(1)).accounts({ campaign: campaignPDA(name), donor: provider.wallet.publicKey }).rpc(),
+        "CampaignEnded"
+      );
+    }
+
+    await program.methods
+      .refund(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA("assert ended failed") })
+      .rpc();
+    await program.methods
+      .withdraw()
+      .accounts({ campaign: campaignPDA("assert ended ok"), approver: null })
+      .rpc();
+  });
+
+  it("3. Claimed campaign: contribute reports AlreadyClaimed", async () => {
+    await program.methods
+      .createCampaign("assert claimed", new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods.setHardCap(true).accounts({ campaign: campaignPDA("assert claimed") }).rpc();
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({ campaign: campaignPDA("assert claimed"), donor: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .withdraw()
+      .accounts({ campaign: campaignPDA("assert claimed"), approver: null })
+      .rpc();
+
+    await expectError(
+      program.methods.contribute(new anchor.BN(1)).accounts({ campaign: campaignPDA("assert claimed"), donor: provider.wallet.publicKey }).rpc(),
+      "AlreadyClaimed"
+    );
+  });
+
+  it("4. Cancelled campaign: contribute and withdraw report CampaignCancelled, refunds pass even with the goal met", async () => {
+    await launch("assert cancelled", 60, 1000000000);
+    await program.methods
+      .setMinAgeBeforeCancel(new anchor.BN(0))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .cancelCampaign()
+      .accounts({ campaign: campaignPDA("assert cancelled"), approver: null })
+      .rpc();
+    await program.methods
+      .setMinAgeBeforeCancel(new anchor.BN(60 * 60))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await expectError(
+      program.methods.contribute(new anchor.BN(1)).accounts({ campaign: campaignPDA("assert cancelled"), donor: provider.wallet.publicKey }).rpc(),
+      "CampaignCancelled"
+    );
+    await expectError(
+      program.methods.withdraw().accounts({ campaign: campaignPDA("assert cancelled"), approver: null }).rpc(),
+      "CampaignCancelled"
+    );
+    await program.methods
+      .refund(new anchor.BN(1000000000))
+      .accounts({ campaign: campaignPDA("assert cancelled") })
+      .rpc();
+  });
+
+  it("5. Finalized campaign: contribute and withdraw report AlreadyClaimed, refund reports GoalMetCannotRefund", async () => {
+    await launch("assert finalized", 3, 1000000000);
+    await program.methods
+      .setAbandonPolicy(new anchor.BN(1), 500)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+    await program.methods
+      .finalizeAbandoned()
+      .accounts({ campaign: campaignPDA("assert finalized"), payout: provider.wallet.publicKey, authority: provider.wallet.publicKey, approver: null })
+      .rpc();
+    await program.methods
+      .setAbandonPolicy(new anchor.BN(365 * 24 * 60 * 60), 500)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+    const account = await program.account.campaign.fetch(campaignPDA("assert finalized"));
+    assert.isTrue(account.finalized);
+
+    await expectError(
+      program.methods.contribute(new anchor.BN(1)).accounts({ campaign: campaignPDA("assert finalized"), donor: provider.wallet.publicKey }).rpc(),
+      "AlreadyClaimed"
+    );
+    await expectError(
+      program.methods.withdraw().accounts({ campaign: campaignPDA("assert finalized"), approver: null }).rpc(),
+      "AlreadyClaimed"
+    );
+    await expectError(
+      program.methods.refund(new anchor.BN(1)).accounts({ campaign: campaignPDA("assert finalized") }).rpc(),
+      "GoalMetCannotRefund"
+    );
+  });
+
+  it("6. Hard cap filled before the deadline: terminal for refunds, contribute reports HardCapExceeded", async () => {
+    await program.methods
+      .createCampaign("assert hard cap", new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods.setHardCap(true).accounts({ campaign: campaignPDA("assert hard cap") }).rpc();
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({ campaign: campaignPDA("assert hard cap"), donor: provider.wallet.publicKey })
+      .rpc();
+
+    await expectError(
+      program.methods.contribute(new anchor.BN(1)).accounts({ campaign: campaignPDA("assert hard cap"), donor: provider.wallet.publicKey }).rpc(),
+      "HardCapExceeded"
+    );
+    await expectError(
+      program.methods.refund(new anchor.BN(1)).accounts({ campaign: campaignPDA("assert hard cap") }).rpc(),
+      "GoalMetCannotRefund"
+    );
+  });
+});
+
+describe("contribute percentage of remaining", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "pct of remaining";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. Contributes 25% of the remaining gap", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(200000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .contributePctOfRemaining(2500)
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.raised.toNumber(), 200000000 + 200000000);
+  });
+
+  it("2. Rejects when the goal is already met", async () => {
+    await program.methods
+      .contributePctOfRemaining(10000)
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .contributePctOfRemaining(2500)
+        .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+        .rpc();
+      assert.fail("contribution with nothing remaining should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "GoalAlreadyMet");
+    }
+  });
+
+  it("3. Rejects a percentage that rounds down to zero lamports", async () => {
+    const name = "pct of remaining dust";
+    const [dustPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    );
+    await program.methods
+      .createCampaign(name, new anchor.BN(1000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .contributePctOfRemaining(1)
+        .accounts({ campaign: dustPDA, donor: provider.wallet.publicKey })
+        .rpc();
+      assert.fail("a zero-lamport contribution should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "PercentageRoundsToZero");
+    }
+  });
+});
+
+describe("charity fallback", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "charity fallback";
+  const charity = anchor.web3.Keypair.generate().publicKey;
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const route = () =>
+    program.methods
+      .routeToCharity()
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey, charity, keeper: provider.wallet.publicKey })
+      .rpc();
+
+  it("1. An unclaimed contribution is not routed before the timeout", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .setCharityFallback(charity)
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+
+    try {
+      await route();
+      assert.fail("routing before the timeout should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "CharityTimeoutNotElapsed");
+    }
+  });
+
+  it("2. After the long timeout the contribution goes to charity and is closed", async () => {
+    await program.methods
+      .setCharityTimeout(new anchor.BN(2))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await route();
+
+    assert.equal(await provider.connection.getBalance(charity), 100000000);
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.contributors, 0);
+    assert.equal(account.raised.toNumber(), 0);
+
+    await program.methods
+      .setCharityTimeout(new anchor.BN(365 * 24 * 60 * 60))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("max active campaigns", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const [statsPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("platform_stats")],
+    program.programId
+  );
+
+  const campaignPDA = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const create = (name: string) =>
+    program.methods
+      .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+  it("1. Creates up to the cap and rejects one more", async () => {
+    const stats = await program.account.platformStats.fetch(statsPDA);
+
+    await program.methods
+      .setMaxActiveCampaigns(stats.activeCampaigns + 2)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await create("active cap a");
+    await create("active cap b");
+
+    try {
+      await create("active cap c");
+      assert.fail("creation above the cap should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "TooManyActiveCampaigns");
+    }
+  });
+
+  it("2. Finalizing a campaign frees a slot", async () => {
+    await program.methods
+      .closeCampaign()
+      .accounts({ campaign: campaignPDA("active cap a") })
+      .rpc();
+
+    await create("active cap c");
+
+    await program.methods
+      .setMaxActiveCampaigns(0)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("has_contributed", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "has_contributed";
+  const stranger = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. Returns true for a donor who has contributed", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    const contributed = await program.methods
+      .hasContributed(campaignPDA, provider.wallet.publicKey)
+      .view();
+    assert.isTrue(contributed);
+  });
+
+  it("2. Returns false for a donor who hasn't", async () => {
+    const contributed = await program.methods
+      .hasContributed(campaignPDA, stranger.publicKey)
+      .view();
+    assert.isFalse(contributed);
+  });
+});
+
+describe("finalize abandoned", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "finalize abandoned";
+  const payout = anchor.web3.Keypair.generate().publicKey;
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const finalize = () =>
+    program.methods
+      .finalizeAbandoned()
+      .accounts({ campaign: campaignPDA, payout, authority: provider.wallet.publicKey })
+      .rpc();
+
+  it("1. The platform cannot finalize before the inactivity window", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .setPayoutAddress(payout)
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+
+    try {
+      await finalize();
+      assert.fail("finalize inside the inactivity window should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "AbandonWindowNotElapsed");
+    }
+  });
+
+  it("2. After the window the platform sweeps to the payout address minus the penalty", async () => {
+    await program.methods
+      .setAbandonPolicy(new anchor.BN(1), 500)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await finalize();
+
+    assert.equal(await provider.connection.getBalance(payout), 950000000);
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.isTrue(account.claimed);
+
+    await program.methods
+      .setAbandonPolicy(new anchor.BN(365 * 24 * 60 * 60), 500)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("insufficient donor funds", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "insufficient donor funds";
+  const poor = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. A donor with insufficient funds gets a clear error", async () => {
+    const sig = await provider.connection.requestAirdrop(poor.publicKey, 20000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .contribute(new anchor.BN(100000000))
+        .accounts({ campaign: campaignPDA, donor: poor.publicKey })
+        .signers([poor])
+        .rpc();
+      assert.fail("underfunded contribution should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "InsufficientDonorFunds");
+    }
+  });
+
+  it("2. A funded donor succeeds", async () => {
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.raised.toNumber(), 100000000);
+  });
+});
+
+describe("cancel campaign", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "cancel campaign";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const cancel = () =>
+    program.methods
+      .cancelCampaign()
+      .accounts({ campaign: campaignPDA, approver: null })
+      .rpc();
+
+  it("1. An immediate cancel is rejected", async () => {
+    await program.methods
+      .setMinAgeBeforeCancel(new anchor.BN(3))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.isAbove(account.createdAt.toNumber(), 0);
+
+    try {
+      await cancel();
+      assert.fail("cancel before the minimum age should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "CancelTooEarly");
+    }
+  });
+
+  it("2. Cancel is allowed after the minimum age and backers can refund", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+
+    await cancel();
+
+    await program.methods
+      .refund(new anchor.BN(1000000000))
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.isTrue(account.cancelled);
+    assert.equal(account.raised.toNumber(), 0);
+
+    await program.methods
+      .setMinAgeBeforeCancel(new anchor.BN(60 * 60))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("first backer incentive", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "first backer incentive";
+  const backer = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. Records the first non-creator backer", async () => {
+    const sig = await provider.connection.requestAirdrop(backer.publicKey, 2000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 4), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .depositFirstBackerIncentive(new anchor.BN(10000000), true)
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(900000000))
+      .accounts({ campaign: campaignPDA, donor: backer.publicKey })
+      .signers([backer])
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.ok(account.firstContributor.equals(backer.publicKey));
+  });
+
+  it("2. The first backer receives the incentive on success", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    const before = await provider.connection.getBalance(backer.publicKey);
+    await program.methods
+      .withdraw()
+      .accounts({ campaign: campaignPDA, approver: null, firstContributor: backer.publicKey })
+      .rpc();
+    const after = await provider.connection.getBalance(backer.publicKey);
+
+    assert.equal(after - before, 10000000);
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.firstBackerIncentive.toNumber(), 0);
+  });
+});
+
+describe("post-deadline contribution", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "post-deadline contribution";
+  const latecomer = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+  const [contributionPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("contribution"), campaignPDA.toBuffer(), latecomer.publicKey.toBuffer()],
+    program.programId
+  );
+
+  it("1. Leaves no contribution account behind and charges no rent", async () => {
+    const sig = await provider.connection.requestAirdrop(latecomer.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 2), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    const before = await provider.connection.getBalance(latecomer.publicKey);
+    try {
+      await program.methods
+        .contribute(new anchor.BN(100000000))
+        .accounts({ campaign: campaignPDA, donor: latecomer.publicKey })
+        .signers([latecomer])
+        .rpc();
+      assert.fail("post-deadline contribution should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "CampaignEnded");
+    }
+
+    assert.isNull(await provider.connection.getAccountInfo(contributionPDA));
+    assert.equal(await provider.connection.getBalance(latecomer.publicKey), before);
+  });
+});
+
+describe("image hash", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "image hash";
+  const stranger = anchor.web3.Keypair.generate();
+  const hash = Array.from({ length: 32 }, (_, i) => i + 1);
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. The creator sets the image hash and it reads back", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .setImageHash(hash)
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.deepEqual(Array.from(account.imageHash), hash);
+  });
+
+  it("2. Only the creator can set it", async () => {
+    try {
+      await program.methods
+        .setImageHash(new Array(32).fill(0))
+        .accounts({ campaign: campaignPDA, creator: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+      assert.fail("non-creator should not set the image hash");
+    } catch (err) {
+      assert.include(err.toString(), "NotCreator");
+    }
+  });
+});
+
+describe("creator match", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "creator match";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const contribute = (amount: number) =>
+    program.methods
+      .contribute(new anchor.BN(amount))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+  it("1. The match doubles early contributions until the pool is exhausted", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(2000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .fundCreatorMatch(new anchor.BN(300000000))
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    await contribute(200000000);
+    let account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.raised.toNumber(), 400000000);
+
+    await contribute(200000000);
+    account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.raised.toNumber(), 700000000);
+    assert.equal(account.creatorMatchPool.toNumber(), 0);
+
+    await contribute(100000000);
+    account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.raised.toNumber(), 800000000);
+    assert.equal(account.creatorMatched.toNumber(), 300000000);
+  });
+});
+
+describe("state log", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "state log";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+  let stateLogPDA: anchor.web3.PublicKey;
+
+  it("1. Records each lifecycle transition in order", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 4), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .initStateLog()
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+    stateLogPDA = await campaignChildPDA(program, "state_log", campaignPDA);
+
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey, stateLog: stateLogPDA })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    await program.methods
+      .refund(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, stateLog: stateLogPDA })
+      .rpc();
+    await program.methods
+      .closeCampaign()
+      .accounts({ campaign: campaignPDA, stateLog: stateLogPDA })
+      .rpc();
+
+    const log = await program.account.stateLog.fetch(stateLogPDA);
+    assert.deepEqual(
+      log.entries.map((e) => Object.keys(e.state)[0]),
+      ["created", "active", "failed", "closed"]
+    );
+    assert.equal(log.total, 4);
+    for (let i = 1; i < log.entries.length; i++) {
+      assert.isAtLeast(log.entries[i].timestamp.toNumber(), log.entries[i - 1].timestamp.toNumber());
+    }
+    assert.ok(log.entries[0].actor.equals(provider.wallet.publicKey));
+  });
+
+  it("2. A donated refund records the failure as well", async () => {
+    const name = "state log donated";
+    const [donatedPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    );
+    await program.methods
+      .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 4), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .initStateLog()
+      .accounts({ campaign: donatedPDA })
+      .rpc();
+    const donatedLogPDA = await campaignChildPDA(program, "state_log", donatedPDA);
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: donatedPDA, donor: provider.wallet.publicKey, stateLog: donatedLogPDA })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    await program.methods
+      .donateRefund()
+      .accounts({ campaign: donatedPDA, creator: provider.wallet.publicKey, stateLog: donatedLogPDA })
+      .rpc();
+
+    const log = await program.account.stateLog.fetch(donatedLogPDA);
+    assert.deepEqual(
+      log.entries.map((e) => Object.keys(e.state)[0]),
+      ["created", "active", "failed"]
+    );
+  });
+
+  it("3. Once opened, the log must be passed and every close path records", async () => {
+    const name = "state log required";
+    const [requiredPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    );
+    const [vaultPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), requiredPDA.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .initStateLog()
+      .accounts({ campaign: requiredPDA })
+      .rpc();
+    const requiredLogPDA = await campaignChildPDA(program, "state_log", requiredPDA);
+
+    try {
+      await program.methods
+        .contribute(new anchor.BN(100000000))
+        .accounts({ campaign: requiredPDA, donor: provider.wallet.publicKey })
+        .rpc();
+      assert.fail("contributing without the state log should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "StateLogRequired");
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+
+    // The batch leaves it alone: it has no way to write the log.
+    await program.methods
+      .expireEmptyBatch()
+      .accounts({ keeper: provider.wallet.publicKey })
+      .remainingAccounts([
+        { pubkey: requiredPDA, isWritable: true, isSigner: false },
+        { pubkey: vaultPDA, isWritable: true, isSigner: false },
+        { pubkey: provider.wallet.publicKey, isWritable: true, isSigner: false },
+      ])
+      .rpc();
+    assert.isNotNull(await provider.connection.getAccountInfo(requiredPDA));
+
+    try {
+      await program.methods
+        .crankCleanup()
+        .accounts({ campaign: requiredPDA, creator: provider.wallet.publicKey, keeper: provider.wallet.publicKey })
+        .rpc();
+      assert.fail("cleaning up without the state log should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "StateLogRequired");
+    }
+
+    await program.methods
+      .crankCleanup()
+      .accounts({ campaign: requiredPDA, creator: provider.wallet.publicKey, keeper: provider.wallet.publicKey, stateLog: requiredLogPDA })
+      .rpc();
+
+    const log = await program.account.stateLog.fetch(requiredLogPDA);
+    assert.deepEqual(
+      log.entries.map((e) => Object.keys(e.state)[0]),
+      ["created", "closed"]
+    );
+  });
+});
+
+describe("cover shortfall", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const [treasuryPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("treasury")],
+    program.programId
+  );
+
+  const campaignPDA = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const createAndFund = async (name: string, amount: number) => {
+    await program.methods
+      .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 4), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(amount))
+      .accounts({ campaign: campaignPDA(name), donor: provider.wallet.publicKey })
+      .rpc();
+  };
+
+  const cover = (name: string) =>
+    program.methods
+      .coverShortfall()
+      .accounts({ campaign: campaignPDA(name), authority: provider.wallet.publicKey })
+      .rpc();
+
+  it("1. Covers a shortfall within the allowed band", async () => {
+    await program.methods
+      .setShortfallPolicy(new anchor.BN(100000000), 1000)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+    const tx = new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: provider.wallet.publicKey,
+        toPubkey: treasuryPDA,
+        lamports: 200000000,
+      })
+    );
+    await provider.sendAndConfirm(tx);
+
+    await createAndFund("shortfall near", 950000000);
+    await createAndFund("shortfall far", 500000000);
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    await cover("shortfall near");
+    const account = await program.account.campaign.fetch(campaignPDA("shortfall near"));
+    assert.equal(account.raised.toNumber(), 1000000000);
+  });
+
+  it("2. Rejects a campaign too far below its goal", async () => {
+    try {
+      await cover("shortfall far");
+      assert.fail("a large shortfall should not be covered");
+    } catch (err) {
+      assert.include(err.toString(), "ShortfallNotCoverable");
+    }
+
+    await program.methods
+      .setShortfallPolicy(new anchor.BN(0), 0)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("goal met", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "goal met boundary";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const contribute = (amount: number) =>
+    program.methods
+      .contribute(new anchor.BN(amount))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+  const goalMet = async () =>
+    (await program.methods.getCampaignInfo().accounts({ campaign: campaignPDA }).view()).goalMet;
+
+  it("1. One lamport short is not met", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(100000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await contribute(99999999);
+
+    assert.isFalse(await goalMet());
+  });
+
+  it("2. Raising exactly the goal counts as met", async () => {
+    await contribute(1);
+
+    assert.isTrue(await goalMet());
+  });
+});
+
+describe("expire empty batch", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const names = ["expire empty 1", "expire empty 2", "expire funded"];
+
+  const campaignPDA = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const vaultPDA = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), campaignPDA(name).toBuffer()],
+      program.programId
+    )[0];
+
+  it("1. Closes expired empty campaigns and skips funded ones", async () => {
+    for (const name of names) {
+      await program.methods
+        .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), null, { uri: "", symbol: "" })
+        .accounts({ creator: provider.wallet.publicKey })
+        .rpc();
+    }
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA("expire funded"), donor: provider.wallet.publicKey })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+
+    const keeper = anchor.web3.Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(keeper.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+    const before = await provider.connection.getBalance(keeper.publicKey);
+
+    await program.methods
+      .expireEmptyBatch()
+      .accounts({ keeper: keeper.publicKey })
+      .remainingAccounts(
+        names.flatMap((name) => [
+          { pubkey: campaignPDA(name), isWritable: true, isSigner: false },
+          { pubkey: vaultPDA(name), isWritable: true, isSigner: false },
+          { pubkey: provider.wallet.publicKey, isWritable: true, isSigner: false },
+        ])
+      )
+      .signers([keeper])
+      .rpc();
+
+    assert.isNull(await provider.connection.getAccountInfo(campaignPDA("expire empty 1")));
+    assert.isNull(await provider.connection.getAccountInfo(campaignPDA("expire empty 2")));
+    const funded = await program.account.campaign.fetch(campaignPDA("expire funded"));
+    assert.equal(funded.raised.toNumber(), 100000000);
+
+    // Two keeper rewards, minus the keeper's own signature fee.
+    const after = await provider.connection.getBalance(keeper.publicKey);
+    assert.equal(after - before, 2 * 5000 - 5000);
+  });
+
+  it("2. Skips an empty campaign that can still roll its deadline", async () => {
+    const name = "expire rolling";
+    await program.methods
+      .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 4), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .setMaxRolls(1)
+      .accounts({ campaign: campaignPDA(name) })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    await program.methods
+      .expireEmptyBatch()
+      .accounts({ keeper: provider.wallet.publicKey })
+      .remainingAccounts([
+        { pubkey: campaignPDA(name), isWritable: true, isSigner: false },
+        { pubkey: vaultPDA(name), isWritable: true, isSigner: false },
+        { pubkey: provider.wallet.publicKey, isWritable: true, isSigner: false },
+      ])
+      .rpc();
+    assert.isNotNull(await provider.connection.getAccountInfo(campaignPDA(name)));
+
+    try {
+      await program.methods
+        .crankCleanup()
+        .accounts({ campaign: campaignPDA(name), creator: provider.wallet.publicKey, keeper: provider.wallet.publicKey })
+        .rpc();
+      assert.fail("a campaign with rolls left should not be cleaned up");
+    } catch (err) {
+      assert.include(err.toString(), "CampaignNotEnded");
+    }
+  });
+});
+
+describe("max contribution share of goal", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "max contribution bps";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const contribute = (amount: number) =>
+    program.methods
+      .contribute(new anchor.BN(amount))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+  it("1. Rejects a single contribution above the allowed share", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .setMaxContributionBpsOfGoal(2500)
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    try {
+      await contribute(300000000);
+      assert.fail("a contribution over 25% of the goal should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "ContributionTooLargeForGoal");
+    }
+  });
+
+  it("2. Allows a contribution at the allowed share", async () => {
+    await contribute(250000000);
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.raised.toNumber(), 250000000);
+  });
+});
+
+describe("contribution histogram", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "contribution histogram";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+  let histogramPDA: anchor.web3.PublicKey;
+
+  const contribute = (amount: number) =>
+    program.methods
+      .contribute(new anchor.BN(amount))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey, histogram: histogramPDA })
+      .rpc();
+
+  it("1. Buckets contributions by period", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    // Three-second "days" so the test can cross a bucket boundary.
+    await program.methods
+      .initHistogram(new anchor.BN(3))
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+    histogramPDA = await campaignChildPDA(program, "histogram", campaignPDA);
+
+    await contribute(10000000);
+    await contribute(20000000);
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+    await contribute(30000000);
+
+    const histogram = await program.account.contributionHistogram.fetch(histogramPDA);
+    const used = histogram.bins.filter((bin) => bin.count > 0);
+    assert.isAtLeast(used.length, 2);
+    assert.equal(used.reduce((total, bin) => total + bin.count, 0), 3);
+    assert.equal(used.reduce((total, bin) => total + bin.sum.toNumber(), 0), 60000000);
+
+    const last = used.reduce((a, b) => (a.periodIndex.gt(b.periodIndex) ? a : b));
+    assert.equal(last.count, 1);
+    assert.equal(last.sum.toNumber(), 30000000);
+  });
+});
+
+describe("funding mode", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "funding mode";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const setMode = (mode: number) =>
+    program.methods
+      .setFundingMode(mode)
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+  it("1. Switches to keep-what-you-raise while empty", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await setMode(1);
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.fundingMode, 1);
+  });
+
+  it("2. Rejects a switch once contributions exist", async () => {
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    try {
+      await setMode(0);
+      assert.fail("switching modes with funds should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "CampaignHasFunds");
+    }
+  });
+
+  it("3. Keeps a keep-what-you-raise campaign below goal from being swept as dust", async () => {
+    const name = "funding mode dust";
+    const [dustPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    );
+    await program.methods
+      .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 5), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods.setFundingMode(1).accounts({ campaign: dustPDA }).rpc();
+    await program.methods.openMicroPool().accounts({ campaign: dustPDA }).rpc();
+    await program.methods
+      .microContribute(new anchor.BN(1000))
+      .accounts({ campaign: dustPDA, donor: provider.wallet.publicKey })
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    try {
+      await program.methods.closeCampaign().accounts({ campaign: dustPDA }).rpc();
+      assert.fail("an unclaimed payable campaign should not close");
+    } catch (err) {
+      assert.include(err.toString(), "CampaignNotTerminal");
+    }
+    try {
+      await program.methods
+        .crankCleanup()
+        .accounts({ campaign: dustPDA, creator: provider.wallet.publicKey, keeper: provider.wallet.publicKey })
+        .rpc();
+      assert.fail("an unclaimed payable campaign should not be cleaned up");
+    } catch (err) {
+      assert.include(err.toString(), "CampaignNotTerminal");
+    }
+  });
+});
+
+describe("vault as recipient", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "vault as recipient";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+  const [vaultPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("vault"), campaignPDA.toBuffer()],
+    program.programId
+  );
+
+  it("1. Rejects the vault as the withdraw payout address", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .setPayoutAddress(vaultPDA)
+        .accounts({ campaign: campaignPDA })
+        .rpc();
+      assert.fail("the vault should not be accepted as a payout address");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidRefundRecipient");
+    }
+  });
+
+  it("2. Rejects the vault as the charity refund fallback", async () => {
+    try {
+      await program.methods
+        .setCharityFallback(vaultPDA)
+        .accounts({ campaign: campaignPDA })
+        .rpc();
+      assert.fail("the vault should not be accepted as a refund recipient");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidRefundRecipient");
+    }
+  });
+});
+
+describe("count threshold", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "count threshold";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const contribute = (amount: number) =>
+    program.methods
+      .contribute(new anchor.BN(amount))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+  it("1. A dust contribution is not counted", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .setCountThreshold(new anchor.BN(50000000))
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    await contribute(10000000);
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.countedContributors, 0);
+    assert.equal(account.contributors, 1);
+  });
+
+  it("2. The donor is counted once their cumulative amount crosses the threshold", async () => {
+    await contribute(30000000);
+    let account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.countedContributors, 0);
+
+    await contribute(10000000);
+    account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.countedContributors, 1);
+
+    await contribute(10000000);
+    account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.countedContributors, 1);
+  });
+});
+
+describe("withdraw and close", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "withdraw and close";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+  const [vaultPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("vault"), campaignPDA.toBuffer()],
+    program.programId
+  );
+
+  const withdrawAndClose = () =>
+    program.methods
+      .withdrawAndClose()
+      .accountsPartial({ withdraw: { campaign: campaignPDA, approver: null } })
+      .rpc();
+
+  it("1. Rejects while contribution accounts remain open", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(100000000), new anchor.BN(Math.floor(Date.now() / 1000) + 4), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    try {
+      await withdrawAndClose();
+      assert.fail("settlement should wait for contribution accounts to close");
+    } catch (err) {
+      assert.include(err.toString(), "ContributionsRemain");
+    }
+  });
+
+  it("2. Withdraws, sweeps and closes an empty successful campaign", async () => {
+    await program.methods
+      .closeContribution()
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    await withdrawAndClose();
+
+    assert.isNull(await provider.connection.getAccountInfo(campaignPDA));
+    assert.equal(await provider.connection.getBalance(vaultPDA), 0);
+  });
+});
+
+describe("contribution cap schedule", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "contribution cap schedule";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const contribute = (amount: number) =>
+    program.methods
+      .contribute(new anchor.BN(amount))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+  it("1. Allows a large contribution early in the window", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 12), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    // 200M cap, halved once less than half the window remains.
+    await program.methods
+      .setContributionCapSchedule(new anchor.BN(200000000), 5000, 5000)
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    await contribute(150000000);
+  });
+
+  it("2. Rejects the same contribution in the final window", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 7000));
+
+    try {
+      await contribute(150000000);
+      assert.fail("the cap should have halved near the deadline");
+    } catch (err) {
+      assert.include(err.toString(), "ContributionCapExceeded");
+    }
+
+    await contribute(100000000);
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.raised.toNumber(), 250000000);
+  });
+
+  it("3. Rejects a schedule change once contributions exist", async () => {
+    try {
+      await program.methods
+        .setContributionCapSchedule(new anchor.BN(0), 0, 0)
+        .accounts({ campaign: campaignPDA })
+        .rpc();
+      assert.fail("schedule change with funds should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "CampaignHasFunds");
+    }
+  });
+});
+
+describe("contribution status", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignPDA = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const create = (name: string, seconds: number, raiseCap = 0) =>
+    program.methods
+      .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + seconds), new anchor.BN(raiseCap), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+  const status = async (name: string) =>
+    Object.keys(await program.methods.contributionStatus().accounts({ campaign: campaignPDA(name) }).view())[0];
+
+  it("1. Open, then frozen", async () => {
+    await create("status frozen", 60);
+    assert.equal(await status("status frozen"), "open");
+
+    await program.methods
+      .freezeContributions()
+      .accounts({ campaign: campaignPDA("status frozen") })
+      .rpc();
+    assert.equal(await status("status frozen"), "frozen");
+  });
+
+  it("2. Raise cap reached", async () => {
+    await create("status raise cap", 60, 1000000000);
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({ campaign: campaignPDA("status raise cap"), donor: provider.wallet.publicKey })
+      .rpc();
+    assert.equal(await status("status raise cap"), "raiseCapReached");
+  });
+
+  it("3. Hard cap reached", async () => {
+    await create("status hard cap", 60);
+    await program.methods.setHardCap(true).accounts({ campaign: campaignPDA("status hard cap") }).rpc();
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({ campaign: campaignPDA("status hard cap"), donor: provider.wallet.publicKey })
+      .rpc();
+    assert.equal(await status("status hard cap"), "hardCapReached");
+  });
+
+  it("4. Cancelled", async () => {
+    await program.methods
+      .setMinAgeBeforeCancel(new anchor.BN(0))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+    await create("status cancelled", 60);
+    await program.methods
+      .cancelCampaign()
+      .accounts({ campaign: campaignPDA("status cancelled"), approver: null })
+      .rpc();
+    assert.equal(await status("status cancelled"), "cancelled");
+
+    await program.methods
+      .setMinAgeBeforeCancel(new anchor.BN(60 * 60))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+
+  it("5. Ended", async () => {
+    await create("status ended", 2);
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    assert.equal(await status("status ended"), "ended");
+  });
+});
+
+describe("payout tranches", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "payout tranches";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const release = () =>
+    program.methods
+      .releaseTranche()
+      .accounts({ campaign: campaignPDA, recipient: provider.wallet.publicKey })
+      .rpc();
+
+  it("1. Withdraw releases only the first tranche", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(400000000), new anchor.BN(Math.floor(Date.now() / 1000) + 4), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .setTrancheSchedule(2, new anchor.BN(4))
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(400000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    await program.methods
+      .withdraw()
+      .accounts({ campaign: campaignPDA, approver: null })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.isAbove(account.trancheTotal.toNumber(), 0);
+    assert.equal(account.trancheReleased.toNumber(), Math.floor(account.trancheTotal.toNumber() / 2));
+  });
+
+  it("2. Rejects an early release", async () => {
+    try {
+      await release();
+      assert.fail("the second tranche should still be locked");
+    } catch (err) {
+      assert.include(err.toString(), "TrancheNotUnlocked");
+    }
+  });
+
+  it("3. Releases the remaining tranche on schedule", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    await release();
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.trancheReleased.toNumber(), account.trancheTotal.toNumber());
+  });
+});
+
+describe("estimate eta", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "estimate eta";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const eta = async () =>
+    (await program.methods.estimateEta().accounts({ campaign: campaignPDA }).view()).toNumber();
+
+  it("1. Returns the sentinel while nothing is raised", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 600), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    assert.equal(await eta(), -1);
+  });
+
+  it("2. Projects the goal from the current velocity", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+    await program.methods
+      .contribute(new anchor.BN(250000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    // A quarter of the goal raised so far, so three more elapsed periods remain.
+    const account = await program.account.campaign.fetch(campaignPDA);
+    const estimate = await eta();
+    const now = await provider.connection.getBlockTime(await provider.connection.getSlot());
+    const elapsed = now - account.createdAt.toNumber();
+    assert.approximately(estimate, now + 3 * elapsed, 3);
+  });
+});
+
+describe("conditional match", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const sponsor = anchor.web3.Keypair.generate();
+
+  const campaignPDA = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const vaultPDA = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), campaignPDA(name).toBuffer()],
+      program.programId
+    )[0];
+
+  const setUp = async (name: string, contribution: number) => {
+    await program.methods
+      .createCampaign(name, new anchor.BN(100000000), new anchor.BN(Math.floor(Date.now() / 1000) + 4), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .depositConditionalMatch(new anchor.BN(50000000))
+      .accounts({ campaign: campaignPDA(name), sponsor: sponsor.publicKey })
+      .signers([sponsor])
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(contribution))
+      .accounts({ campaign: campaignPDA(name), donor: provider.wallet.publicKey })
+      .rpc();
+  };
+
+  it("1. Releases the match to the creator on success", async () => {
+    const sig = await provider.connection.requestAirdrop(sponsor.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await setUp("conditional success", 100000000);
+    await setUp("conditional failure", 10000000);
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    const vaultBefore = await provider.connection.getBalance(vaultPDA("conditional success"));
+    const creatorBefore = await provider.connection.getBalance(provider.wallet.publicKey);
+    await program.methods
+      .releaseConditionalMatch()
+      .accounts({ campaign: campaignPDA("conditional success"), creator: provider.wallet.publicKey, sponsor: sponsor.publicKey })
+      .rpc();
+    const creatorAfter = await provider.connection.getBalance(provider.wallet.publicKey);
+
+    // The creator also pays the 5000-lamport transaction fee.
+    assert.equal(creatorAfter - creatorBefore, 50000000 - 5000);
+    assert.equal(await provider.connection.getBalance(vaultPDA("conditional success")), vaultBefore);
+    const account = await program.account.campaign.fetch(campaignPDA("conditional success"));
+    assert.equal(account.raised.toNumber(), 100000000);
+    assert.equal(account.conditionalMatchOutstanding.toNumber(), 0);
+  });
+
+  it("2. Returns the match to the sponsor on failure", async () => {
+    try {
+      await program.methods
+        .releaseConditionalMatch()
+        .accounts({ campaign: campaignPDA("conditional failure"), creator: provider.wallet.publicKey, sponsor: sponsor.publicKey })
+        .rpc();
+      assert.fail("a failed campaign should not receive the match");
+    } catch (err) {
+      assert.include(err.toString(), "CampaignNotSuccessful");
+    }
+
+    const before = await provider.connection.getBalance(sponsor.publicKey);
+    await program.methods
+      .returnConditionalMatch()
+      .accounts({ campaign: campaignPDA("conditional failure"), sponsor: sponsor.publicKey })
+      .rpc();
+    const after = await provider.connection.getBalance(sponsor.publicKey);
+
+    assert.isAtLeast(after - before, 50000000);
+  });
+});
+
+describe("donor blocklist", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "donor blocklist";
+  const donor = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const contribute = () =>
+    program.methods
+      .contribute(new anchor.BN(10000000))
+      .accounts({ campaign: campaignPDA, donor: donor.publicKey })
+      .signers([donor])
+      .rpc();
+
+  it("1. A blocked donor is rejected", async () => {
+    const sig = await provider.connection.requestAirdrop(donor.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .blockDonor(donor.publicKey)
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    try {
+      await contribute();
+      assert.fail("a blocked donor should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "DonorBlocked");
+    }
+  });
+
+  it("2. Unblocking lets the donor contribute again", async () => {
+    await program.methods
+      .unblockDonor(donor.publicKey)
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    await contribute();
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.raised.toNumber(), 10000000);
+  });
+});
+
+describe("refund outstanding", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "refund outstanding";
+  const backer = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const outstanding = () =>
+    program.methods.getRefundOutstanding().accounts({ campaign: campaignPDA }).view();
+
+  it("1. Nothing is outstanding while the campaign is live", async () => {
+    const sig = await provider.connection.requestAirdrop(backer.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 4), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(200000000))
+      .accounts({ campaign: campaignPDA, donor: backer.publicKey })
+      .signers([backer])
+      .rpc();
+
+    const result = await outstanding();
+    assert.equal(result.totalRefundable.toNumber(), 0);
+    assert.equal(result.openContributions, 0);
+  });
+
+  it("2. Tracks the outstanding total as refunds are processed", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    let result = await outstanding();
+    assert.equal(result.totalRefundable.toNumber(), 300000000);
+    assert.equal(result.openContributions, 2);
+
+    await program.methods
+      .refund(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+    result = await outstanding();
+    assert.equal(result.totalRefundable.toNumber(), 200000000);
+    assert.equal(result.openContributions, 1);
+
+    await program.methods
+      .refund(new anchor.BN(200000000))
+      .accounts({ campaign: campaignPDA, donor: backer.publicKey })
+      .signers([backer])
+      .rpc();
+    result = await outstanding();
+    assert.equal(result.totalRefundable.toNumber(), 0);
+    assert.equal(result.openContributions, 0);
+  });
+
+  it("3. Leaves the creator's match out of the total", async () => {
+    const matchedName = "refund outstanding matched";
+    const [matchedPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(matchedName)],
+      program.programId
+    );
+    const matchedOutstanding = () =>
+      program.methods.getRefundOutstanding().accounts({ campaign: matchedPDA }).view();
+
+    await program.methods
+      .createCampaign(matchedName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 4), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .fundCreatorMatch(new anchor.BN(50000000))
+      .accounts({ campaign: matchedPDA })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: matchedPDA, donor: provider.wallet.publicKey })
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    let result = await matchedOutstanding();
+    assert.equal(result.totalRefundable.toNumber(), 100000000);
+    assert.equal(result.openContributions, 1);
+
+    await program.methods
+      .refund(new anchor.BN(100000000))
+      .accounts({ campaign: matchedPDA })
+      .rpc();
+    result = await matchedOutstanding();
+    assert.equal(result.totalRefundable.toNumber(), 0);
+    assert.equal(result.openContributions, 0);
+  });
+});
+
+describe("clock drift tolerance", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "clock drift tolerance";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const refund = () =>
+    program.methods
+      .refund(new anchor.BN(200000000))
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+  it("1. Contributions are accepted just past the deadline within tolerance", async () => {
+    await program.methods
+      .setClockDriftTolerance(new anchor.BN(6))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 2), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+  });
+
+  it("2. Refunds agree and wait for the same tolerant boundary", async () => {
+    try {
+      await refund();
+      assert.fail("refunds should wait until the tolerant deadline");
+    } catch (err) {
+      assert.include(err.toString(), "CampaignNotEnded");
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    try {
+      await program.methods
+        .contribute(new anchor.BN(100000000))
+        .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+        .rpc();
+      assert.fail("contributions should close at the tolerant deadline");
+    } catch (err) {
+      assert.include(err.toString(), "CampaignEnded");
+    }
+    await refund();
+
+    await program.methods
+      .setClockDriftTolerance(new anchor.BN(0))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("transfer contribution", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignPDA = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const create = (name: string, goal: number) =>
+    program.methods
+      .createCampaign(name, new anchor.BN(goal), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+  const transfer = (from: string, to: string) =>
+    program.methods
+      .transferContribution(campaignPDA(to))
+      .accounts({ source: campaignPDA(from), target: campaignPDA(to) })
+      .rpc();
+
+  it("1. Moves a live contribution to another live campaign", async () => {
+    await create("transfer source", 1000000000);
+    await create("transfer target", 1000000000);
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA("transfer source"), donor: provider.wallet.publicKey })
+      .rpc();
+
+    await transfer("transfer source", "transfer target");
+
+    const source = await program.account.campaign.fetch(campaignPDA("transfer source"));
+    const target = await program.account.campaign.fetch(campaignPDA("transfer target"));
+    assert.equal(source.raised.toNumber(), 0);
+    assert.equal(source.contributors, 0);
+    assert.equal(target.raised.toNumber(), 100000000);
+    assert.equal(target.contributors, 1);
+    assert.ok(target.firstContributor.equals(provider.wallet.publicKey));
+
+    const [contributionPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("contribution"), campaignPDA("transfer target").toBuffer(), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const contribution = await program.account.contribution.fetch(contributionPDA);
+    assert.equal(contribution.amount.toNumber(), 100000000);
+  });
+
+  it("2. Respects the target's hard cap", async () => {
+    await create("transfer capped", 50000000);
+    await program.methods.setHardCap(true).accounts({ campaign: campaignPDA("transfer capped") }).rpc();
+
+    try {
+      await transfer("transfer target", "transfer capped");
+      assert.fail("moving past the target's hard cap should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "HardCapExceeded");
+    }
+  });
+});
+
+describe("withdrawn invariant", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "withdrawn invariant";
+  const stray = 50000000;
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+  const [vaultPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("vault"), campaignPDA.toBuffer()],
+    program.programId
+  );
+
+  const injectStray = async () => {
+    const tx = new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({ fromPubkey: provider.wallet.publicKey, toPubkey: vaultPDA, lamports: stray })
+    );
+    await provider.sendAndConfirm(tx);
+  };
+
+  it("1. The first tranche never pays out stray vault lamports", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(200000000), new anchor.BN(Math.floor(Date.now() / 1000) + 4), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .setTrancheSchedule(2, new anchor.BN(4))
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(200000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+    await injectStray();
+
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+    await program.methods
+      .withdraw()
+      .accounts({ campaign: campaignPDA, approver: null })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.isAtMost(account.trancheTotal.toNumber(), 200000000);
+    assert.isAtMost(account.withdrawn.toNumber(), account.raised.toNumber());
+  });
+
+  it("2. Later partial releases stop at the amount raised", async () => {
+    await injectStray();
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    await program.methods
+      .releaseTranche()
+      .accounts({ campaign: campaignPDA, recipient: provider.wallet.publicKey })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.withdrawn.toNumber(), account.raised.toNumber());
+    assert.isAtLeast(await provider.connection.getBalance(vaultPDA), 2 * stray);
+  });
+});
+
+describe("campaign comments", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "campaign comments";
+  const stranger = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const commentPDA = (index: number) => {
+    const seed = Buffer.alloc(4);
+    seed.writeUInt32LE(index);
+    return campaignChildPDA(program, "comment", campaignPDA, seed);
+  };
+
+  it("1. A backer can post a comment", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(10000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .postComment("Good luck!")
+      .accounts({ campaign: campaignPDA, comment: await commentPDA(0), author: provider.wallet.publicKey })
+      .rpc();
+
+    const comment = await program.account.comment.fetch(await commentPDA(0));
+    assert.equal(comment.content, "Good luck!");
+    assert.isFalse(comment.hidden);
+  });
+
+  it("2. A non-backer cannot comment", async () => {
+    const sig = await provider.connection.requestAirdrop(stranger.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    try {
+      await program.methods
+        .postComment("spam")
+        .accounts({ campaign: campaignPDA, comment: await commentPDA(1), author: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+      assert.fail("a non-backer should not be able to comment");
+    } catch (err) {
+      assert.include(err.toString(), "AccountNotInitialized");
+    }
+  });
+
+  it("3. The creator can hide a comment", async () => {
+    await program.methods
+      .hideComment(true)
+      .accounts({ campaign: campaignPDA, comment: await commentPDA(0) })
+      .rpc();
+
+    const comment = await program.account.comment.fetch(await commentPDA(0));
+    assert.isTrue(comment.hidden);
+  });
+});
+
+describe("create and seed", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "create and seed";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+  const [vaultPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("vault"), campaignPDA.toBuffer()],
+    program.programId
+  );
+
+  it("1. Creates the campaign with the creator's seed contribution", async () => {
+    await program.methods
+      .createAndSeed(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), { uri: "", symbol: "" }, new anchor.BN(100000000))
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.raised.toNumber(), 100000000);
+    assert.equal(account.contributors, 1);
+    assert.ok(account.firstContributor.equals(provider.wallet.publicKey));
+    assert.equal(await provider.connection.getBalance(vaultPDA), 100000000);
+
+    const [contributionPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("contribution"), campaignPDA.toBuffer(), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const contribution = await program.account.contribution.fetch(contributionPDA);
+    assert.equal(contribution.amount.toNumber(), 100000000);
+  });
+
+  it("2. A seed that meets the goal emits GoalReached", async () => {
+    const name = "create and seed goal";
+    const [goalPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    );
+
+    let reached = null;
+    const listener = program.addEventListener("goalReached", (event) => {
+      if (event.campaign.equals(goalPDA)) {
+        reached = event;
+      }
+    });
+
+    await program.methods
+      .createAndSeed(name, new anchor.BN(100000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), { uri: "", symbol: "" }, new anchor.BN(100000000))
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+    await program.removeEventListener(listener);
+
+    const account = await program.account.campaign.fetch(goalPDA);
+    assert.isTrue(account.goalExactlyMet);
+    assert.isNotNull(reached);
+    assert.ok(reached.raised.eq(account.goal));
+  });
+});
+
+describe("soft close", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const pdas = (name: string) => {
+    const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    );
+    const [contributionPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("contribution"), campaignPDA.toBuffer(), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    return { campaignPDA, contributionPDA };
+  };
+
+  const createWithWindow = async (name: string, window: number) => {
+    const { campaignPDA } = pdas(name);
+    await program.methods
+      .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .setSoftCloseWindow(new anchor.BN(window))
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(10000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+  };
+
+  it("1. Contributions inside the soft-close window are accepted and marked late", async () => {
+    await createWithWindow("soft close late", 55);
+
+    const { campaignPDA, contributionPDA } = pdas("soft close late");
+    const contribution = await program.account.contribution.fetch(contributionPDA);
+    assert.equal(contribution.amount.toNumber(), 10000000);
+    assert.isTrue(contribution.late);
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.raised.toNumber(), 10000000);
+  });
+
+  it("2. Contributions before the window are not marked late", async () => {
+    await createWithWindow("soft close early", 5);
+
+    const contribution = await program.account.contribution.fetch(pdas("soft close early").contributionPDA);
+    assert.isFalse(contribution.late);
+  });
+
+  it("3. Rejects a window longer than the campaign", async () => {
+    await program.methods
+      .createCampaign("soft close invalid", new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    try {
+      await program.methods
+        .setSoftCloseWindow(new anchor.BN(3600))
+        .accounts({ campaign: pdas("soft close invalid").campaignPDA })
+        .rpc();
+      assert.fail("should have rejected the window");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidSoftCloseWindow");
+    }
+  });
+});
+
+describe("vault integrity", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "vault integrity";
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+  const [vaultPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("vault"), campaignPDA.toBuffer()],
+    program.programId
+  );
+
+  const verify = () =>
+    program.methods.verifyVaultIntegrity().accounts({ campaign: campaignPDA }).view();
+
+  it("1. The vault matches raised minus withdrawn", async () => {
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(20000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+
+    const integrity = await verify();
+    assert.isTrue(integrity.consistent);
+    assert.equal(integrity.expected.toNumber(), 20000000);
+    assert.equal(integrity.discrepancy.toNumber(), 0);
+  });
+
+  it("2. Detects a stray deposit into the vault", async () => {
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: provider.wallet.publicKey,
+          toPubkey: vaultPDA,
+          lamports: 5000000,
+        })
+      )
+    );
+
+    const integrity = await verify();
+    assert.isFalse(integrity.consistent);
+    assert.equal(integrity.actual.toNumber(), 25000000);
+    assert.equal(integrity.discrepancy.toNumber(), 5000000);
+  });
+});
+
+describe("cancel refund crank", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "cancel refund crank";
+
+  const backers = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const contributionOf = (donor: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("contribution"), campaignPDA.toBuffer(), donor.toBuffer()],
+      program.programId
+    )[0];
+
+  const loyaltyOf = (donor: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("loyalty"), donor.toBuffer()],
+      program.programId
+    )[0];
+
+  it("1. Crank refunds every backer of a cancelled campaign with their contribution rent", async () => {
+    for (const kp of backers) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 1000000000);
+      await provider.connection.confirmTransaction(sig);
+    }
+    await program.methods
+      .setMinAgeBeforeCancel(new anchor.BN(0))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(5000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    for (const kp of backers) {
+      await program.methods
+        .contribute(new anchor.BN(100000000))
+        .accounts({ campaign: campaignPDA, donor: kp.publicKey })
+        .signers([kp])
+        .rpc();
+    }
+    await program.methods
+      .cancelCampaign()
+      .accounts({ campaign: campaignPDA, approver: null })
+      .rpc();
+
+    const rent = (await provider.connection.getAccountInfo(contributionOf(backers[0].publicKey))).lamports;
+    const before = await Promise.all(backers.map((kp) => provider.connection.getBalance(kp.publicKey)));
+
+    await program.methods
+      .processAutoRefunds()
+      .accounts({ campaign: campaignPDA, keeper: provider.wallet.publicKey })
+      .remainingAccounts(
+        backers.flatMap((kp) => [
+          { pubkey: contributionOf(kp.publicKey), isWritable: true, isSigner: false },
+          { pubkey: kp.publicKey, isWritable: true, isSigner: false },
+          { pubkey: loyaltyOf(kp.publicKey), isWritable: true, isSigner: false },
+        ])
+      )
+      .rpc();
+
+    for (let i = 0; i < backers.length; i++) {
+      const after = await provider.connection.getBalance(backers[i].publicKey);
+      assert.isAtLeast(after - before[i], 100000000 + rent);
+      assert.isNull(await provider.connection.getAccountInfo(contributionOf(backers[i].publicKey)));
+    }
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.ok(account.raised.eqn(0));
+    assert.equal(account.contributors, 0);
+
+    await program.methods
+      .setMinAgeBeforeCancel(new anchor.BN(60 * 60))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("is authorized", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "is authorized";
+
+  // Action 2 is reserved.
+  const [WITHDRAW, CANCEL, UPDATE, MODERATE] = [0, 1, 3, 4];
+
+  const creator = anchor.web3.Keypair.generate();
+  const stranger = anchor.web3.Keypair.generate();
+  const approver = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), creator.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const authorized = (action: number, signer?: anchor.web3.Keypair) =>
+    program.methods
+      .isAuthorized(action)
+      .accounts({ campaign: campaignPDA, signer: signer ? signer.publicKey : provider.wallet.publicKey })
+      .signers(signer ? [signer] : [])
+      .view();
+
+  it("1. The creator may manage but not moderate the campaign", async () => {
+    const sig = await provider.connection.requestAirdrop(creator.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: creator.publicKey })
+      .signers([creator])
+      .rpc();
+
+    for (const action of [WITHDRAW, CANCEL, UPDATE]) {
+      assert.isTrue(await authorized(action, creator));
+    }
+    assert.isFalse(await authorized(MODERATE, creator));
+  });
+
+  it("2. A random key is authorized for nothing", async () => {
+    for (const action of [WITHDRAW, CANCEL, UPDATE, MODERATE]) {
+      assert.isFalse(await authorized(action, stranger));
+    }
+  });
+
+  it("3. The platform authority may only moderate", async () => {
+    for (const action of [WITHDRAW, CANCEL, UPDATE]) {
+      assert.isFalse(await authorized(action));
+    }
+    assert.isTrue(await authorized(MODERATE));
+  });
+
+  it("4. Rejects the reserved action and an unknown one", async () => {
+    for (const action of [2, 99]) {
+      try {
+        await authorized(action, creator);
+        assert.fail("unknown action should be rejected");
+      } catch (err) {
+        assert.include(err.toString(), "UnknownAction");
+      }
+    }
+  });
+
+  it("5. Withdraw and cancel need the approver when the campaign requires one", async () => {
+    const name = "is authorized dual";
+    const [dualPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    );
+    await program.methods
+      .createCampaignDual(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), { uri: "", symbol: "" }, true)
+      .accounts({ creator: provider.wallet.publicKey, approver: approver.publicKey })
+      .signers([approver])
+      .rpc();
+
+    for (const action of [WITHDRAW, CANCEL]) {
+      const alone = await program.methods
+        .isAuthorized(action)
+        .accounts({ campaign: dualPDA, signer: provider.wallet.publicKey, approver: null })
+        .view();
+      assert.isFalse(alone);
+      const cosigned = await program.methods
+        .isAuthorized(action)
+        .accounts({ campaign: dualPDA, signer: provider.wallet.publicKey, approver: approver.publicKey })
+        .signers([approver])
+        .view();
+      assert.isTrue(cosigned);
+    }
+  });
+
+  it("6. The backup creator is not authorized before the inactivity timeout", async () => {
+    const name = "is authorized backup";
+    const [backupPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    );
+    await program.methods
+      .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), stranger.publicKey, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    const early = await program.methods
+      .isAuthorized(WITHDRAW)
+      .accounts({ campaign: backupPDA, signer: stranger.publicKey })
+      .signers([stranger])
+      .view();
+    assert.isFalse(early);
+  });
+});
+
+describe("goal increment", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const create = (name: string, goal: number) =>
+    program.methods
+      .createCampaign(name, new anchor.BN(goal), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+  it("1. An aligned goal is accepted", async () => {
+    await program.methods
+      .setGoalIncrement(new anchor.BN(100000000))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await create("goal increment aligned", 1200000000);
+    const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from("goal increment aligned")],
+      program.programId
+    );
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.goal.toNumber(), 1200000000);
+  });
+
+  it("2. A misaligned goal is rejected", async () => {
+    try {
+      await create("goal increment misaligned", 1250000000);
+      assert.fail("misaligned goal should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "GoalNotAligned");
+    }
+
+    await program.methods
+      .setGoalIncrement(new anchor.BN(1))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("contribution delegate", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "contribution delegate";
+
+  const donor = anchor.web3.Keypair.generate();
+  const sessionKey = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+  const [delegatePDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("contribution_delegate"), donor.publicKey.toBuffer(), sessionKey.publicKey.toBuffer()],
+    program.programId
+  );
+  const [contributionPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("contribution"), campaignPDA.toBuffer(), donor.publicKey.toBuffer()],
+    program.programId
+  );
+  const [loyaltyPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("loyalty"), donor.publicKey.toBuffer()],
+    program.programId
+  );
+
+  const contributeDelegated = (amount: number) =>
+    program.methods
+      .contributeDelegated(new anchor.BN(amount))
+      .accountsPartial({ campaign: campaignPDA, delegate: delegatePDA, contribution: contributionPDA, loyalty: loyaltyPDA, sessionKey: sessionKey.publicKey })
+      .signers([sessionKey])
+      .rpc();
+
+  it("1. A session key contributes within its allowance on the donor's behalf", async () => {
+    for (const kp of [donor, sessionKey]) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 1000000000);
+      await provider.connection.confirmTransaction(sig);
+    }
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .approveContributionDelegate(sessionKey.publicKey, new anchor.BN(50000000))
+      .accounts({ donor: donor.publicKey })
+      .signers([donor])
+      .rpc();
+    await program.methods
+      .fundCreatorMatch(new anchor.BN(10000000))
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+
+    await contributeDelegated(30000000);
+
+    const contribution = await program.account.contribution.fetch(contributionPDA);
+    assert.ok(contribution.donor.equals(donor.publicKey));
+    assert.equal(contribution.amount.toNumber(), 30000000);
+    const delegate = await program.account.contributionDelegate.fetch(delegatePDA);
+    assert.equal(delegate.spent.toNumber(), 30000000);
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.raised.toNumber(), 40000000);
+    assert.equal(account.creatorMatched.toNumber(), 10000000);
+    const loyalty = await program.account.loyaltyAccount.fetch(loyaltyPDA);
+    assert.ok(loyalty.donor.equals(donor.publicKey));
+    assert.equal(loyalty.points.toNumber(), 30);
+  });
+
+  it("2. Rejects a contribution beyond the remaining allowance", async () => {
+    try {
+      await contributeDelegated(30000000);
+      assert.fail("should exceed the allowance");
+    } catch (err) {
+      assert.include(err.toString(), "DelegateAllowanceExceeded");
+    }
+  });
+
+  it("3. Revoking returns the unspent allowance to the donor", async () => {
+    const before = await provider.connection.getBalance(donor.publicKey);
+    await program.methods
+      .revokeContributionDelegate()
+      .accounts({ delegate: delegatePDA, donor: donor.publicKey })
+      .signers([donor])
+      .rpc();
+
+    const after = await provider.connection.getBalance(donor.publicKey);
+    assert.isAtLeast(after - before, 20000000);
+    assert.isNull(await provider.connection.getAccountInfo(delegatePDA));
+  });
+});
+
+describe("lock until", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignPDA = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const create = (name: string, seconds: number) =>
+    program.methods
+      .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + seconds), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+  const refund = () =>
+    program.methods
+      .refund(new anchor.BN(10000000))
+      .accounts({ campaign: campaignPDA("lock until"), donor: provider.wallet.publicKey })
+      .rpc();
+
+  it("1. Blocks moving a contribution out before the lock", async () => {
+    await create("lock until", 4);
+    await create("lock until target", 60);
+    await program.methods
+      .setLockUntil(new anchor.BN(Math.floor(Date.now() / 1000) + 10))
+      .accounts({ campaign: campaignPDA("lock until") })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(10000000))
+      .accounts({ campaign: campaignPDA("lock until"), donor: provider.wallet.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .transferContribution(campaignPDA("lock until target"))
+        .accounts({ source: campaignPDA("lock until"), target: campaignPDA("lock until target") })
+        .rpc();
+      assert.fail("a locked contribution should not move");
+    } catch (err) {
+      assert.include(err.toString(), "FundsLocked");
+    }
+  });
+
+  it("2. Blocks refunds of a failed campaign before the lock", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+    try {
+      await refund();
+      assert.fail("refund should be locked");
+    } catch (err) {
+      assert.include(err.toString(), "FundsLocked");
+    }
+  });
+
+  it("3. Allows the refund once the lock has passed", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 7000));
+    await refund();
+
+    const account = await program.account.campaign.fetch(campaignPDA("lock until"));
+    assert.ok(account.raised.eqn(0));
+  });
+
+  it("4. Rejects a lock more than 30 days past the deadline", async () => {
+    const deadline = Math.floor(Date.now() / 1000) + 60;
+    await program.methods
+      .createCampaign("lock until cap", new anchor.BN(1000000000), new anchor.BN(deadline), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .setLockUntil(new anchor.BN(deadline + 31 * 24 * 60 * 60))
+        .accounts({ campaign: campaignPDA("lock until cap") })
+        .rpc();
+      assert.fail("lock past the cap should fail");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "LockTooLong");
+    }
+    await program.methods
+      .setLockUntil(new anchor.BN(deadline + 30 * 24 * 60 * 60))
+      .accounts({ campaign: campaignPDA("lock until cap") })
+      .rpc();
+  });
+
+  it("5. A cancelled campaign refunds before the lock", async () => {
+    await create("lock until cancelled", 60);
+    await program.methods
+      .setLockUntil(new anchor.BN(Math.floor(Date.now() / 1000) + 60 * 60))
+      .accounts({ campaign: campaignPDA("lock until cancelled") })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(10000000))
+      .accounts({ campaign: campaignPDA("lock until cancelled"), donor: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .setMinAgeBeforeCancel(new anchor.BN(0))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .cancelCampaign()
+      .accounts({ campaign: campaignPDA("lock until cancelled"), approver: null })
+      .rpc();
+    await program.methods
+      .setMinAgeBeforeCancel(new anchor.BN(60 * 60))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .refund(new anchor.BN(10000000))
+      .accounts({ campaign: campaignPDA("lock until cancelled"), donor: provider.wallet.publicKey })
+      .rpc();
+    const account = await program.account.campaign.fetch(campaignPDA("lock until cancelled"));
+    assert.ok(account.raised.eqn(0));
+  });
+
+  it("6. Blocks salvaging a near-miss before the lock", async () => {
+    await create("lock until salvage", 3);
+    await program.methods
+      .setLockUntil(new anchor.BN(Math.floor(Date.now() / 1000) + 60 * 60))
+      .accounts({ campaign: campaignPDA("lock until salvage") })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(950000000))
+      .accounts({ campaign: campaignPDA("lock until salvage"), donor: provider.wallet.publicKey })
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+
+    try {
+      await program.methods
+        .salvage(new anchor.BN(950000000))
+        .accounts({ campaign: campaignPDA("lock until salvage") })
+        .rpc();
+      assert.fail("salvage should be locked");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "FundsLocked");
+    }
+  });
+});
+
+describe("voting power", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "voting power";
+
+  const voter = anchor.web3.Keypair.generate();
+  const delegators = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+  const other = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const contributionOf = (donor: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("contribution"), campaignPDA.toBuffer(), donor.toBuffer()],
+      program.programId
+    )[0];
+
+  it("1. A delegate's power aggregates their own and delegated contributions", async () => {
+    for (const kp of [voter, other, ...delegators]) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 1000000000);
+      await provider.connection.confirmTransaction(sig);
+    }
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(5000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: voter.publicKey })
+      .signers([voter])
+      .rpc();
+    for (const [i, kp] of delegators.entries()) {
+      await program.methods
+        .contributeAndDelegate(new anchor.BN(20000000 * (i + 1)), voter.publicKey)
+        .accounts({ campaign: campaignPDA, donor: kp.publicKey })
+        .signers([kp])
+        .rpc();
+    }
+    await program.methods
+      .contribute(new anchor.BN(70000000))
+      .accounts({ campaign: campaignPDA, donor: other.publicKey })
+      .signers([other])
+      .rpc();
+
+    const power = await program.methods
+      .getVotingPower(voter.publicKey)
+      .accounts({ campaign: campaignPDA })
+      .remainingAccounts(
+        [voter, other, ...delegators].map((kp) => ({ pubkey: contributionOf(kp.publicKey), isWritable: false, isSigner: false }))
+      )
+      .view();
+    assert.equal(power.toNumber(), 100000000 + 20000000 + 40000000);
+  });
+
+  it("2. A donor who delegated away has no power of their own", async () => {
+    const power = await program.methods
+      .getVotingPower(delegators[0].publicKey)
+      .accounts({ campaign: campaignPDA })
+      .remainingAccounts([{ pubkey: contributionOf(delegators[0].publicKey), isWritable: false, isSigner: false }])
+      .view();
+    assert.equal(power.toNumber(), 0);
+  });
+});
+
+describe("emergency withdraw", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "emergency withdraw";
+
+  const creator = anchor.web3.Keypair.generate();
+  const backer = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), creator.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const approve = () =>
+    program.methods
+      .approveEmergencyWithdraw(new anchor.BN(40000000))
+      .accounts({ campaign: campaignPDA, authority: provider.wallet.publicKey })
+      .rpc();
+
+  const emergencyWithdraw = (amount: number) =>
+    program.methods
+      .emergencyWithdraw(new anchor.BN(amount))
+      .accounts({ campaign: campaignPDA, authority: provider.wallet.publicKey, creator: creator.publicKey, approver: null })
+      .signers([creator])
+      .rpc();
+
+  it("1. Rejects approval for an unverified campaign and withdrawal before approval", async () => {
+    for (const kp of [creator, backer]) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 1000000000);
+      await provider.connection.confirmTransaction(sig);
+    }
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 10), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: creator.publicKey })
+      .signers([creator])
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: backer.publicKey })
+      .signers([backer])
+      .rpc();
+
+    try {
+      await approve();
+      assert.fail("an unverified campaign should not be approved");
+    } catch (err) {
+      assert.include(err.toString(), "CampaignNotVerified");
+    }
+
+    await program.methods
+      .setVerified(true)
+      .accounts({ campaign: campaignPDA, authority: provider.wallet.publicKey })
+      .rpc();
+    try {
+      await emergencyWithdraw(40000000);
+      assert.fail("unapproved emergency withdrawal should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "EmergencyNotApproved");
+    }
+  });
+
+  it("2. Pays out an approved emergency withdrawal up to the cap before the deadline", async () => {
+    await approve();
+
+    const before = await provider.connection.getBalance(creator.publicKey);
+    await emergencyWithdraw(40000000);
+    const after = await provider.connection.getBalance(creator.publicKey);
+    assert.equal(after - before, 40000000);
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.emergencyWithdrawn.toNumber(), 40000000);
+
+    try {
+      await emergencyWithdraw(1);
+      assert.fail("should exceed the approved amount");
+    } catch (err) {
+      assert.include(err.toString(), "EmergencyCapExceeded");
+    }
+  });
+
+  it("3. Rejects new contributions after an emergency withdrawal", async () => {
+    try {
+      await program.methods
+        .contribute(new anchor.BN(10000000))
+        .accounts({ campaign: campaignPDA, donor: backer.publicKey })
+        .signers([backer])
+        .rpc();
+      assert.fail("contributing after an emergency withdrawal should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "EmergencyWithdrawalTaken");
+    }
+
+    const status = await program.methods.contributionStatus().accounts({ campaign: campaignPDA }).view();
+    assert.equal(Object.keys(status)[0], "emergencyWithdrawn");
+  });
+
+  it("4. Rejects moving a contribution out after an emergency withdrawal", async () => {
+    await program.methods
+      .createCampaign("emergency transfer target", new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    const [targetPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from("emergency transfer target")],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .transferContribution(targetPDA)
+        .accounts({ source: campaignPDA, target: targetPDA, donor: backer.publicKey })
+        .signers([backer])
+        .rpc();
+      assert.fail("transferring after an emergency withdrawal should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "EmergencyWithdrawalTaken");
+    }
+  });
+
+  it("5. Backers refund the remainder if the campaign fails", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 11000));
+
+    try {
+      await emergencyWithdraw(1);
+      assert.fail("an ended campaign should not allow an emergency withdrawal");
+    } catch (err) {
+      assert.include(err.toString(), "CampaignEnded");
+    }
+
+    const before = await provider.connection.getBalance(backer.publicKey);
+    await program.methods
+      .refund(new anchor.BN(100000000))
+      .accounts({ campaign: campaignPDA, donor: backer.publicKey })
+      .signers([backer])
+      .rpc();
+    const after = await provider.connection.getBalance(backer.publicKey);
+    assert.isAtLeast(after - before, 60000000);
+    assert.isBelow(after - before, 100000000);
+  });
+});
+
+describe("batch set uri", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const other = anchor.web3.Keypair.generate();
+  const names = ["batch uri 0", "batch uri 1", "batch uri 2"];
+
+  const campaignPDA = (creator: anchor.web3.PublicKey, name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), creator.toBuffer(), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const writable = (pubkey: anchor.web3.PublicKey) => ({ pubkey, isWritable: true, isSigner: false });
+
+  it("1. Updates the URI on all of the creator's campaigns", async () => {
+    for (const name of names) {
+      await program.methods
+        .createCampaign(name, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "https://old.example", symbol: "" })
+        .accounts({ creator: provider.wallet.publicKey })
+        .rpc();
+    }
+
+    await program.methods
+      .batchSetUri("https://new.example")
+      .accounts({ creator: provider.wallet.publicKey })
+      .remainingAccounts(names.map((name) => writable(campaignPDA(provider.wallet.publicKey, name))))
+      .rpc();
+
+    for (const name of names) {
+      const account = await program.account.campaign.fetch(campaignPDA(provider.wallet.publicKey, name));
+      assert.equal(account.uri, "https://new.example");
+    }
+  });
+
+  it("2. Rejects the whole batch when one campaign belongs to another creator", async () => {
+    const sig = await provider.connection.requestAirdrop(other.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+    await program.methods
+      .createCampaign("batch uri other", new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(0), null, { uri: "", symbol: "" })
+      .accounts({ creator: other.publicKey })
+      .signers([other])
+      .rpc();
+
+    try {
+      await program.methods
+        .batchSetUri("https://hijack.example")
+        .accounts({ creator: provider.wallet.publicKey })
+        .remainingAccounts([
+          writable(campaignPDA(provider.wallet.publicKey, names[0])),
+          writable(campaignPDA(other.publicKey, "batch uri other")),
+        ])
+        .rpc();
+      assert.fail("a foreign campaign should fail the batch");
+    } catch (err) {
+      assert.include(err.toString(), "NotCreator");
+    }
+
+    const account = await program.account.campaign.fetch(campaignPDA(provider.wallet.publicKey, names[0]));
+    assert.equal(account.uri, "https://new.example");
+  });
+});
+
+describe("validate contribution", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "validate contribution";
+
+  const blocked = anchor.web3.Keypair.generate();
+  const unfunded = anchor.web3.Keypair.generate();
+  const rentless = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  const validate = (amount: number, donor = provider.wallet.publicKey) =>
+    program.methods
+      .validateContribution(new anchor.BN(amount))
+      .accounts({ campaign: campaignPDA, donor })
+      .rpc();
+
+  const expectError = async (amount: number, error: string, donor?: anchor.web3.PublicKey) => {
+    try {
+      await validate(amount, donor);
+      assert.fail(`expected ${error}`);
+    } catch (err) {
+      assert.include(err.toString(), error);
+    }
+  };
+
+  it("1. A valid amount passes", async () => {
+    const sig = await provider.connection.requestAirdrop(blocked.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 60), new anchor.BN(1500000000), null, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+
+    await validate(10000000);
+  });
+
+  it("2. Reports the error the real contribution would raise", async () => {
+    await expectError(2000000000, "RaiseCapExceeded");
+    await expectError(10000000, "InsufficientDonorFunds", unfunded.publicKey);
+
+    // Covers the amount but not the first-time contribution and loyalty rent.
+    const sig = await provider.connection.requestAirdrop(rentless.publicKey, 10001000);
+    await provider.connection.confirmTransaction(sig);
+    await expectError(10000000, "InsufficientDonorFunds", rentless.publicKey);
+
+    await program.methods.blockDonor(blocked.publicKey).accounts({ campaign: campaignPDA }).rpc();
+    await expectError(10000000, "DonorBlocked", blocked.publicKey);
+
+    await program.methods.setMaxContributionBpsOfGoal(1000).accounts({ campaign: campaignPDA }).rpc();
+    await expectError(200000000, "ContributionTooLargeForGoal");
+
+    await program.methods.setHardCap(true).accounts({ campaign: campaignPDA }).rpc();
+    await expectError(1200000000, "HardCapExceeded");
+
+    await program.methods.freezeContributions().accounts({ campaign: campaignPDA }).rpc();
+    await expectError(10000000, "ContributionsFrozen");
+    await program.methods.unfreezeContributions().accounts({ campaign: campaignPDA }).rpc();
+  });
+
+  it("3. Moves no funds", async () => {
+    await validate(10000000);
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.ok(account.raised.eqn(0));
+  });
+});
+
+describe("backup tranche vesting", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "backup tranche vesting";
+  const backup = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. A backup takeover releases only the first tranche", async () => {
+    const sig = await provider.connection.requestAirdrop(backup.publicKey, 1000000000);
+    await provider.connection.confirmTransaction(sig);
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(400000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), backup.publicKey, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .setTrancheSchedule(2, new anchor.BN(4))
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(400000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .setBackupInactivityTimeout(new anchor.BN(1))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    const before = await provider.connection.getBalance(backup.publicKey);
+    await program.methods
+      .backupWithdraw()
+      .accounts({ campaign: campaignPDA, backupCreator: backup.publicKey })
+      .signers([backup])
+      .rpc();
+    const after = await provider.connection.getBalance(backup.publicKey);
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.trancheReleased.toNumber(), Math.floor(account.trancheTotal.toNumber() / 2));
+    assert.equal(after - before, account.trancheReleased.toNumber());
+    assert.ok(account.trancheRecipient.equals(backup.publicKey));
+
+    await program.methods
+      .setBackupInactivityTimeout(new anchor.BN(180 * 24 * 60 * 60))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+
+  it("2. Later tranches go to the backup creator, whoever releases them", async () => {
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    try {
+      await program.methods
+        .releaseTranche()
+        .accounts({ campaign: campaignPDA, recipient: provider.wallet.publicKey })
+        .rpc();
+      assert.fail("the original creator should not receive a backup tranche");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidPayoutAddress");
+    }
+
+    const before = await provider.connection.getBalance(backup.publicKey);
+    await program.methods
+      .releaseTranche()
+      .accounts({ campaign: campaignPDA, recipient: backup.publicKey })
+      .rpc();
+    const after = await provider.connection.getBalance(backup.publicKey);
+
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.equal(account.trancheReleased.toNumber(), account.trancheTotal.toNumber());
+    assert.equal(after - before, account.trancheTotal.toNumber() - Math.floor(account.trancheTotal.toNumber() / 2));
+  });
+});
+
+describe("backup under review", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "backup under review";
+  const backup = anchor.web3.Keypair.generate();
+  const reporter = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. The backup creator cannot withdraw a campaign under review", async () => {
+    for (const kp of [backup, reporter]) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 100000000);
+      await provider.connection.confirmTransaction(sig);
+    }
+    await program.methods
+      .setReportThreshold(1)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .setBackupInactivityTimeout(new anchor.BN(1))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), backup.publicKey, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .reportCampaign()
+      .accounts({ campaign: campaignPDA, reporter: reporter.publicKey })
+      .signers([reporter])
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    try {
+      await program.methods
+        .backupWithdraw()
+        .accounts({ campaign: campaignPDA, backupCreator: backup.publicKey })
+        .signers([backup])
+        .rpc();
+      assert.fail("backup withdraw under review should be blocked");
+    } catch (err) {
+      assert.include(err.toString(), "CampaignUnderReview");
+    }
+
+    await program.methods
+      .setReportThreshold(0)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .setBackupInactivityTimeout(new anchor.BN(180 * 24 * 60 * 60))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("abandoned payout approver", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "abandoned payout approver";
+  const approver = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. Settling without the creator still requires the approver co-signature", async () => {
+    await program.methods
+      .createCampaignDual(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), { uri: "", symbol: "" }, true)
+      .accounts({ creator: provider.wallet.publicKey, approver: approver.publicKey })
+      .signers([approver])
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({ campaign: campaignPDA, donor: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .setAbandonPolicy(new anchor.BN(1), 500)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    try {
+      await program.methods
+        .finalizeAbandoned()
+        .accounts({ campaign: campaignPDA, payout: provider.wallet.publicKey, authority: provider.wallet.publicKey, approver: null })
+        .rpc();
+      assert.fail("finalize without the approver should fail");
+    } catch (err) {
+      assert.include(err.toString(), "ApproverSignatureRequired");
+    }
+
+    await program.methods
+      .finalizeAbandoned()
+      .accounts({ campaign: campaignPDA, payout: provider.wallet.publicKey, authority: provider.wallet.publicKey, approver: approver.publicKey })
+      .signers([approver])
+      .rpc();
+    const account = await program.account.campaign.fetch(campaignPDA);
+    assert.isTrue(account.claimed);
+
+    await program.methods
+      .setAbandonPolicy(new anchor.BN(365 * 24 * 60 * 60), 500)
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});
+
+describe("first backer incentive on takeover", () => {
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SolanaCrowdfunding as Program<SolanaCrowdfunding>;
+
+  const campaignName = "first backer takeover";
+  const backup = anchor.web3.Keypair.generate();
+  const backer = anchor.web3.Keypair.generate();
+
+  const [campaignPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("campaign"), provider.wallet.publicKey.toBuffer(), Buffer.from(campaignName)],
+    program.programId
+  );
+
+  it("1. The first backer receives the incentive when the backup creator settles", async () => {
+    for (const kp of [backup, backer]) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 2000000000);
+      await provider.connection.confirmTransaction(sig);
+    }
+    await program.methods
+      .createCampaign(campaignName, new anchor.BN(1000000000), new anchor.BN(Math.floor(Date.now() / 1000) + 3), new anchor.BN(0), backup.publicKey, { uri: "", symbol: "" })
+      .accounts({ creator: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .depositFirstBackerIncentive(new anchor.BN(10000000), false)
+      .accounts({ campaign: campaignPDA })
+      .rpc();
+    await program.methods
+      .contribute(new anchor.BN(1000000000))
+      .accounts({ campaign: campaignPDA, donor: backer.publicKey })
+      .signers([backer])
+      .rpc();
+    await program.methods
+      .setBackupInactivityTimeout(new anchor.BN(1))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+
+    try {
+      await program.methods
+        .backupWithdraw()
+        .accounts({ campaign: campaignPDA, backupCreator: backup.publicKey })
+        .signers([backup])
+        .rpc();
+      assert.fail("settling without the first backer should fail");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidFirstContributor");
+    }
+
+    const before = await provider.connection.getBalance(backer.publicKey);
+    await program.methods
+      .backupWithdraw()
+      .accounts({ campaign: campaignPDA, backupCreator: backup.publicKey, firstContributor: backer.publicKey })
+      .signers([backup])
+      .rpc();
+    const after = await provider.connection.getBalance(backer.publicKey);
+    assert.equal(after - before, 10000000);
+
+    await program.methods
+      .setBackupInactivityTimeout(new anchor.BN(180 * 24 * 60 * 60))
+      .accounts({ authority: provider.wallet.publicKey })
+      .rpc();
+  });
+});